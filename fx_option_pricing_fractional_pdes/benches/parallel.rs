@@ -0,0 +1,24 @@
+// Serial against parallel history accumulation on the same grids. Run with
+// cargo bench --features parallel --bench parallel
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+
+use fx_option_pricing_fractional_pdes::fractional_pde::{FxOptionParams, SolverConfig, solve_fx_tfbs_with};
+
+fn history_threads(c: &mut Criterion) {
+    let params = FxOptionParams::eurusd_call();
+    let mut group = c.benchmark_group("history");
+    group.sample_size(10);
+    for (m, n) in [(400, 200), (800, 800), (1600, 1600)] {
+        for parallel in [false, true] {
+            let config = SolverConfig { parallel, ..SolverConfig::new(m, n) };
+            let name = if parallel { "parallel" } else { "serial" };
+            group.bench_with_input(BenchmarkId::new(name, format!("{m}x{n}")), &config, |b, config| {
+                b.iter(|| solve_fx_tfbs_with(&params, config).unwrap().price_at(1.10))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, history_threads);
+criterion_main!(benches);
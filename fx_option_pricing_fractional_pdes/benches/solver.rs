@@ -0,0 +1,109 @@
+// solve_fx_tfbs_final_stable over a grid of (M, N, alpha). Besides criterion's reports, the mean
+// time per solve of every point is fitted to t = c * M^p * N^q for each alpha by least squares in
+// log space; the exponents are printed and written to <target>/criterion/solver_scaling.csv.
+// The exact L1 history makes every step O(M * step), so p = 1 and q = 2 are expected. Run with
+// cargo bench --bench solver
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use criterion::{BenchmarkId, Criterion, criterion_group};
+
+use fx_option_pricing_fractional_pdes::fractional_pde::{FxOptionParams, solve_fx_tfbs_final_stable};
+
+const SPATIAL_STEPS: [usize; 3] = [100, 200, 400];
+const TIME_STEPS: [usize; 3] = [100, 200, 400];
+const ALPHAS: [f64; 3] = [0.5, 0.85, 1.0];
+
+// (alpha, M, N) of one benchmark point
+type Point = (f64, usize, usize);
+
+// Total time and solve count of each point over all of criterion's samples
+static TIMINGS: Mutex<Vec<(Point, Duration, u64)>> = Mutex::new(Vec::new());
+
+fn record(key: Point, elapsed: Duration, iters: u64) {
+    let mut timings = TIMINGS.lock().unwrap();
+    match timings.iter_mut().find(|(k, _, _)| *k == key) {
+        Some((_, total, count)) => {
+            *total += elapsed;
+            *count += iters;
+        }
+        None => timings.push((key, elapsed, iters)),
+    }
+}
+
+fn solver_grid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solver");
+    group.sample_size(10);
+    for alpha in ALPHAS {
+        let params = FxOptionParams { alpha, ..FxOptionParams::eurusd_call() };
+        for m in SPATIAL_STEPS {
+            for n in TIME_STEPS {
+                group.bench_with_input(BenchmarkId::new(format!("alpha={alpha}"), format!("{m}x{n}")), &(m, n), |b, &(m, n)| {
+                    b.iter_custom(|iters| {
+                        let start = Instant::now();
+                        for _ in 0..iters {
+                            std::hint::black_box(solve_fx_tfbs_final_stable(params.clone(), m, n).unwrap());
+                        }
+                        let elapsed = start.elapsed();
+                        record((alpha, m, n), elapsed, iters);
+                        elapsed
+                    })
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+// Least-squares exponents (p, q) of ln t = ln c + p ln M + q ln N
+fn fit_exponents(points: &[(usize, usize, f64)]) -> (f64, f64) {
+    // Normal equations of the three-parameter linear model, solved by Cramer's rule
+    let rows: Vec<[f64; 4]> = points.iter().map(|&(m, n, t)| [1.0, (m as f64).ln(), (n as f64).ln(), t.ln()]).collect();
+    let mut a = [[0.0; 3]; 3];
+    let mut b = [0.0; 3];
+    for r in &rows {
+        for i in 0..3 {
+            b[i] += r[i] * r[3];
+            for j in 0..3 { a[i][j] += r[i] * r[j]; }
+        }
+    }
+    let det = |m: [[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1]) - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let replaced = |col: usize| {
+        let mut m = a;
+        for i in 0..3 { m[i][col] = b[i]; }
+        det(m)
+    };
+    let d = det(a);
+    (replaced(1) / d, replaced(2) / d)
+}
+
+fn report_scaling() {
+    let timings = TIMINGS.lock().unwrap();
+    let mut csv = String::from("alpha,m_exponent,n_exponent\n");
+    for alpha in ALPHAS {
+        let points: Vec<(usize, usize, f64)> = timings
+            .iter()
+            .filter(|((a, _, _), _, count)| *a == alpha && *count > 0)
+            .map(|&((_, m, n), total, count)| (m, n, total.as_secs_f64() / count as f64))
+            .collect();
+        if points.len() < 3 { continue; }
+        let (p, q) = fit_exponents(&points);
+        println!("alpha {alpha}: time ~ M^{p:.2} * N^{q:.2}");
+        csv.push_str(&format!("{alpha},{p:.4},{q:.4}\n"));
+    }
+    let dir = std::path::PathBuf::from(std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_string())).join("criterion");
+    if std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(dir.join("solver_scaling.csv"), csv)).is_err() {
+        eprintln!("could not write the scaling exponents to {}", dir.display());
+    }
+}
+
+criterion_group!(benches, solver_grid);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    report_scaling();
+}
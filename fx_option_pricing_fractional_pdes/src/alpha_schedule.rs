@@ -0,0 +1,52 @@
+// Time-varying fractional order for the variable-order Caputo derivative. alpha is a function of
+// calendar time t in [0, T] (years from today); the solver evaluates it at each new time level
+// and rebuilds the L1 weights and the implicit matrix for that step.
+
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub enum AlphaSchedule {
+    // (start time, alpha) pairs sorted by start time; each alpha holds until the next start.
+    // Times before the first start use the first alpha
+    PiecewiseConstant(Vec<(f64, f64)>),
+    Function(Arc<dyn Fn(f64) -> f64 + Send + Sync>),
+}
+
+impl AlphaSchedule {
+    pub fn from_fn(f: impl Fn(f64) -> f64 + Send + Sync + 'static) -> Self {
+        AlphaSchedule::Function(Arc::new(f))
+    }
+
+    pub fn alpha_at(&self, t: f64) -> f64 {
+        match self {
+            AlphaSchedule::PiecewiseConstant(pieces) => {
+                let j = pieces.partition_point(|&(start, _)| start <= t);
+                pieces[j.saturating_sub(1)].1
+            }
+            AlphaSchedule::Function(f) => f(t),
+        }
+    }
+
+    // The same schedule moved up by h everywhere (capped at 1), used for the alpha sensitivity
+    pub fn shifted(&self, h: f64) -> Self {
+        match self {
+            AlphaSchedule::PiecewiseConstant(pieces) => {
+                AlphaSchedule::PiecewiseConstant(pieces.iter().map(|&(t, a)| (t, (a + h).min(1.0))).collect())
+            }
+            AlphaSchedule::Function(f) => {
+                let f = f.clone();
+                AlphaSchedule::from_fn(move |t| (f(t) + h).min(1.0))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for AlphaSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlphaSchedule::PiecewiseConstant(pieces) => f.debug_tuple("PiecewiseConstant").field(pieces).finish(),
+            AlphaSchedule::Function(_) => f.write_str("Function(..)"),
+        }
+    }
+}
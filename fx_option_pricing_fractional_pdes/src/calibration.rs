@@ -0,0 +1,274 @@
+// Least-squares calibration of (sigma, alpha) to a set of quoted option premiums across strikes
+// and expiries. Residuals are model minus market premium; the fit uses Levenberg-Marquardt with a
+// forward-difference Jacobian, and parameter standard errors come from s^2 (J^T J)^-1 at the optimum.
+// Quotes are read offline from CSV with columns expiry,strike,option_type,premium,gk_vol where each
+// row carries either a premium or a Garman-Kohlhagen vol (converted to a premium at the given spot).
+
+use std::fmt;
+use std::fs::File;
+
+use serde::Deserialize;
+
+use crate::fractional_pde::{FxOptionParams, SolverConfig, SolverError, solve_fx_tfbs_with};
+use crate::garman_kohlhagen::garman_kohlhagen;
+use crate::payoff::OptionType;
+
+const MAX_ITER: usize = 50;
+const SIGMA_BOUNDS: (f64, f64) = (1e-3, 3.0);
+const ALPHA_BOUNDS: (f64, f64) = (0.05, 1.0);
+const JACOBIAN_STEP: f64 = 1e-4;
+const STEP_TOL: f64 = 1e-8;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SmileQuote {
+    pub t: f64, pub k: f64, pub option_type: OptionType, pub premium: f64,
+}
+
+pub struct CalibrationResult {
+    pub sigma: f64,
+    pub alpha: f64,
+    // Model minus market premium for each quote, in input order
+    pub residuals: Vec<f64>,
+    pub rmse: f64,
+    // Standard errors from the Gauss-Newton covariance; None with fewer than three quotes
+    pub sigma_std_err: Option<f64>,
+    pub alpha_std_err: Option<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+#[derive(Debug)]
+pub enum CalibrationError {
+    Io(std::io::Error),
+    Csv(csv::Error),
+    // A row with an unknown option type, a non-positive expiry or strike, a negative or non-finite
+    // premium or vol, or neither a premium nor a vol
+    BadQuote { row: usize, reason: String },
+    NoQuotes,
+    NonFinitePrice,
+    // The residuals at the starting point square to a NaN or infinite cost
+    NonFiniteCost,
+    Solver(SolverError),
+    // The fit cannot vary sigma and alpha with this feature set (an alpha(t) schedule overrides alpha)
+    Unsupported { feature: &'static str },
+}
+
+impl fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalibrationError::Io(e) => write!(f, "cannot open quote file: {e}"),
+            CalibrationError::Csv(e) => write!(f, "cannot parse quote file: {e}"),
+            CalibrationError::BadQuote { row, reason } => write!(f, "quote row {row}: {reason}"),
+            CalibrationError::NoQuotes => write!(f, "no quotes to calibrate to"),
+            CalibrationError::NonFinitePrice => write!(f, "solver returned a non-finite price"),
+            CalibrationError::NonFiniteCost => write!(f, "initial sum of squared residuals is not finite"),
+            CalibrationError::Solver(e) => write!(f, "solver failed: {e}"),
+            CalibrationError::Unsupported { feature } => write!(f, "calibration does not support {feature}"),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {}
+
+#[derive(Debug, Deserialize)]
+struct QuoteRow {
+    expiry: f64,
+    strike: f64,
+    option_type: String,
+    premium: Option<f64>,
+    gk_vol: Option<f64>,
+}
+
+// Reads quotes from CSV. Vol quotes are turned into premiums with Garman-Kohlhagen at `spot`
+// using the rates in `base`.
+pub fn read_quotes(path: &str, base: &FxOptionParams, spot: f64) -> Result<Vec<SmileQuote>, CalibrationError> {
+    let file = File::open(path).map_err(CalibrationError::Io)?;
+    let mut rdr = csv::Reader::from_reader(file);
+    let mut quotes = Vec::new();
+    for (row, record) in rdr.deserialize::<QuoteRow>().enumerate() {
+        let record = record.map_err(CalibrationError::Csv)?;
+        let bad = |reason: &str| Err(CalibrationError::BadQuote { row, reason: reason.to_string() });
+        if !(record.expiry > 0.0 && record.expiry.is_finite()) { return bad("expiry must be positive"); }
+        if !(record.strike > 0.0 && record.strike.is_finite()) { return bad("strike must be positive"); }
+        if let Some(p) = record.premium && !(p >= 0.0 && p.is_finite()) { return bad("premium must be non-negative and finite"); }
+        if let Some(vol) = record.gk_vol && !(vol > 0.0 && vol.is_finite()) { return bad("gk_vol must be positive and finite"); }
+        let option_type = match record.option_type.trim().to_ascii_lowercase().as_str() {
+            "call" => OptionType::Call,
+            "put" => OptionType::Put,
+            "digital_call" => OptionType::DigitalCall,
+            "digital_put" => OptionType::DigitalPut,
+            other => return Err(CalibrationError::BadQuote { row, reason: format!("unknown option type '{other}'") }),
+        };
+        let premium = match (record.premium, record.gk_vol) {
+            (Some(p), _) => p,
+            (None, Some(vol)) => {
+                let quote_params = FxOptionParams { t: record.expiry, k: record.strike, sigma: vol, option_type, ..base.clone() };
+                garman_kohlhagen(&quote_params, spot).price
+            }
+            (None, None) => return bad("needs a premium or gk_vol"),
+        };
+        quotes.push(SmileQuote { t: record.expiry, k: record.strike, option_type, premium });
+    }
+    Ok(quotes)
+}
+
+// Fits sigma and alpha starting from the values in `base`; rates, s_max, exercise and barrier
+// come from `base`, expiry, strike and payoff from each quote. A schedule in `base` would replace
+// the fitted alpha, so it is rejected.
+pub fn calibrate_sigma_alpha(
+    base: &FxOptionParams, config: &SolverConfig, spot: f64, quotes: &[SmileQuote],
+) -> Result<CalibrationResult, CalibrationError> {
+    if quotes.is_empty() { return Err(CalibrationError::NoQuotes); }
+    if base.alpha_schedule.is_some() { return Err(CalibrationError::Unsupported { feature: "alpha(t) schedules" }); }
+
+    let residuals = |sigma: f64, alpha: f64| -> Result<Vec<f64>, CalibrationError> {
+        quotes.iter().map(|q| {
+            let params = FxOptionParams { t: q.t, k: q.k, option_type: q.option_type, sigma, alpha, ..base.clone() };
+            let model = solve_fx_tfbs_with(&params, config).map_err(CalibrationError::Solver)?.price_at(spot);
+            if model.is_finite() { Ok(model - q.premium) } else { Err(CalibrationError::NonFinitePrice) }
+        }).collect()
+    };
+    let sse = |r: &[f64]| r.iter().map(|x| x * x).sum::<f64>();
+
+    let (mut sigma, mut alpha) = (base.sigma.clamp(SIGMA_BOUNDS.0, SIGMA_BOUNDS.1), base.alpha.clamp(ALPHA_BOUNDS.0, ALPHA_BOUNDS.1));
+    let mut r = residuals(sigma, alpha)?;
+    let mut cost = sse(&r);
+    if !cost.is_finite() { return Err(CalibrationError::NonFiniteCost); }
+    let mut lambda = 1e-3;
+    let mut converged = false;
+    let mut iterations = 0;
+    let mut jac = jacobian(&residuals, &r, sigma, alpha)?;
+
+    while iterations < MAX_ITER {
+        iterations += 1;
+        let (jtj, jtr) = normal_equations(&jac, &r);
+        // Marquardt scaling of the diagonal keeps the step well-posed when alpha barely moves the price
+        let a = [[jtj[0][0] * (1.0 + lambda), jtj[0][1]], [jtj[1][0], jtj[1][1] * (1.0 + lambda)]];
+        let Some(step) = solve_2x2(a, [-jtr[0], -jtr[1]]) else { break };
+        let trial_sigma = (sigma + step[0]).clamp(SIGMA_BOUNDS.0, SIGMA_BOUNDS.1);
+        let trial_alpha = (alpha + step[1]).clamp(ALPHA_BOUNDS.0, ALPHA_BOUNDS.1);
+        let trial_r = residuals(trial_sigma, trial_alpha)?;
+        let trial_cost = sse(&trial_r);
+        if trial_cost < cost {
+            let moved = (trial_sigma - sigma).abs().max((trial_alpha - alpha).abs());
+            (sigma, alpha, r, cost) = (trial_sigma, trial_alpha, trial_r, trial_cost);
+            lambda = (lambda / 3.0).max(1e-12);
+            // Refreshed before the exit test too, so the covariance below is taken at the optimum
+            jac = jacobian(&residuals, &r, sigma, alpha)?;
+            if moved < STEP_TOL { converged = true; break; }
+        } else {
+            // No downhill step even at a vanishing step length: stalled, not converged
+            lambda *= 4.0;
+            if lambda > 1e12 { break; }
+        }
+    }
+
+    let dof = quotes.len().saturating_sub(2);
+    let (jtj, _) = normal_equations(&jac, &r);
+    let cov = if dof > 0 { invert_2x2(jtj).map(|inv| inv.map(|row| row.map(|x| x * cost / dof as f64))) } else { None };
+    Ok(CalibrationResult {
+        sigma,
+        alpha,
+        rmse: (cost / quotes.len() as f64).sqrt(),
+        residuals: r,
+        sigma_std_err: cov.map(|c| c[0][0].sqrt()),
+        alpha_std_err: cov.map(|c| c[1][1].sqrt()),
+        iterations,
+        converged,
+    })
+}
+
+// Forward differences; alpha steps downwards so it never leaves (0, 1]
+fn jacobian(
+    residuals: &impl Fn(f64, f64) -> Result<Vec<f64>, CalibrationError>, r: &[f64], sigma: f64, alpha: f64,
+) -> Result<Vec<[f64; 2]>, CalibrationError> {
+    let r_sigma = residuals(sigma + JACOBIAN_STEP, alpha)?;
+    let r_alpha = residuals(sigma, alpha - JACOBIAN_STEP)?;
+    Ok((0..r.len()).map(|i| [(r_sigma[i] - r[i]) / JACOBIAN_STEP, (r[i] - r_alpha[i]) / JACOBIAN_STEP]).collect())
+}
+
+fn normal_equations(jac: &[[f64; 2]], r: &[f64]) -> ([[f64; 2]; 2], [f64; 2]) {
+    let mut jtj = [[0.0; 2]; 2];
+    let mut jtr = [0.0; 2];
+    for (row, ri) in jac.iter().zip(r) {
+        for a in 0..2 {
+            jtr[a] += row[a] * ri;
+            for b in 0..2 { jtj[a][b] += row[a] * row[b]; }
+        }
+    }
+    (jtj, jtr)
+}
+
+fn invert_2x2(a: [[f64; 2]; 2]) -> Option<[[f64; 2]; 2]> {
+    let det = a[0][0] * a[1][1] - a[0][1] * a[1][0];
+    if det.abs() < f64::MIN_POSITIVE || !det.is_finite() { return None; }
+    Some([[a[1][1] / det, -a[0][1] / det], [-a[1][0] / det, a[0][0] / det]])
+}
+
+fn solve_2x2(a: [[f64; 2]; 2], b: [f64; 2]) -> Option<[f64; 2]> {
+    let inv = invert_2x2(a)?;
+    Some([inv[0][0] * b[0] + inv[0][1] * b[1], inv[1][0] * b[0] + inv[1][1] * b[1]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alpha_schedule::AlphaSchedule;
+
+    #[test]
+    fn recovers_sigma_and_alpha_from_model_quotes() {
+        let config = SolverConfig::new(150, 60);
+        let truth = FxOptionParams { sigma: 0.12, alpha: 0.7, ..FxOptionParams::eurusd_call() };
+        let quotes: Vec<SmileQuote> = [(0.5, 1.05, OptionType::Put), (0.5, 1.10, OptionType::Call), (0.5, 1.15, OptionType::Call),
+            (1.0, 1.00, OptionType::Put), (1.0, 1.10, OptionType::Call), (1.0, 1.20, OptionType::Call)]
+            .iter()
+            .map(|&(t, k, option_type)| {
+                let params = FxOptionParams { t, k, option_type, ..truth.clone() };
+                SmileQuote { t, k, option_type, premium: solve_fx_tfbs_with(&params, &config).unwrap().price_at(1.10) }
+            })
+            .collect();
+
+        let fit = calibrate_sigma_alpha(&FxOptionParams::eurusd_call(), &config, 1.10, &quotes).unwrap();
+        assert!(fit.converged, "stopped after {} iterations", fit.iterations);
+        assert!((fit.sigma - truth.sigma).abs() < 1e-5, "sigma {}", fit.sigma);
+        assert!((fit.alpha - truth.alpha).abs() < 1e-4, "alpha {}", fit.alpha);
+        assert!(fit.rmse < 1e-8, "rmse {}", fit.rmse);
+
+        let schedule = AlphaSchedule::PiecewiseConstant(vec![(0.0, 0.85)]);
+        let scheduled = FxOptionParams { alpha_schedule: Some(schedule), ..FxOptionParams::eurusd_call() };
+        let result = calibrate_sigma_alpha(&scheduled, &config, 1.10, &quotes);
+        assert!(matches!(result, Err(CalibrationError::Unsupported { feature: "alpha(t) schedules" })));
+
+        let unpriceable = [SmileQuote { premium: f64::NAN, ..quotes[0] }];
+        let result = calibrate_sigma_alpha(&FxOptionParams::eurusd_call(), &config, 1.10, &unpriceable);
+        assert!(matches!(result, Err(CalibrationError::NonFiniteCost)));
+    }
+
+    #[test]
+    fn reads_the_smile_file_as_gk_premiums() {
+        let base = FxOptionParams::eurusd_call();
+        let quotes = read_quotes("smile_quotes.csv", &base, 1.10).unwrap();
+        assert_eq!(quotes.len(), 10);
+        let atm = quotes.iter().find(|q| q.t == 1.0 && q.k == 1.10).unwrap();
+        assert_eq!(atm.option_type, OptionType::Call);
+        let gk = garman_kohlhagen(&FxOptionParams { sigma: 0.15, ..base }, 1.10).price;
+        assert!((atm.premium - gk).abs() < 1e-15, "{} vs {gk}", atm.premium);
+        assert!(quotes.iter().all(|q| q.premium > 0.0));
+    }
+
+    #[test]
+    fn malformed_quote_rows_are_rejected_with_their_row() {
+        let path = std::env::temp_dir().join(format!("bad_quotes_{}.csv", std::process::id()));
+        let read = |row: &str| {
+            std::fs::write(&path, format!("expiry,strike,option_type,premium,gk_vol\n0.5,1.10,call,0.03,\n{row}\n")).unwrap();
+            read_quotes(path.to_str().unwrap(), &FxOptionParams::eurusd_call(), 1.10)
+        };
+        assert_eq!(read("1.0,1.10,put,,0.15").unwrap().len(), 2);
+        for row in ["0.0,1.10,call,0.03,", "-1.0,1.10,call,0.03,", "1.0,0.0,call,0.03,", "1.0,-1.10,call,,0.15",
+            "1.0,1.10,call,-0.01,", "1.0,1.10,call,NaN,", "1.0,1.10,call,inf,", "1.0,1.10,call,,-0.15", "1.0,1.10,call,,NaN",
+            "1.0,1.10,straddle,0.03,", "1.0,1.10,call,,"] {
+            assert!(matches!(read(row), Err(CalibrationError::BadQuote { row: 1, .. })), "{row}");
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
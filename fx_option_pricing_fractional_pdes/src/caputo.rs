@@ -0,0 +1,207 @@
+// Time discretisations of the Caputo derivative on a uniform mesh. Both schemes write
+// d * D^alpha u(t_n) = lead * u_n - known(u_0 .. u_(n-1)) with d = Gamma(2-alpha) * dt^alpha,
+// so the implicit step only needs the leading coefficient and the known history.
+//
+// L1 interpolates u linearly on every interval and has order 2 - alpha for smooth u.
+// L1-2 (Gao, Sun & Zhang 2014) keeps the linear piece on the first interval and uses the
+// quadratic through t_(k-2), t_(k-1), t_k on every later one, giving order 3 - alpha.
+// At alpha = 1 it reduces to BDF2 started by one implicit Euler step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeScheme { L1, L1_2 }
+
+pub struct CaputoWeights {
+    pub scheme: TimeScheme,
+    // b_j = (j+1)^(1-alpha) - j^(1-alpha), the integral of the kernel over the interval j steps back
+    pub b: Vec<f64>,
+    // L1-2 only: c_j = (j + 1/2) b_j - (1-alpha)/(2-alpha) ((j+1)^(2-alpha) - j^(2-alpha)), the
+    // kernel moment multiplying the second difference of the quadratic on that interval
+    pub c: Vec<f64>,
+}
+
+impl CaputoWeights {
+    pub fn new(scheme: TimeScheme, alpha: f64, n: usize) -> Self {
+        let b = l1_weights(alpha, n);
+        let c = match scheme {
+            TimeScheme::L1 => Vec::new(),
+            TimeScheme::L1_2 => (0..=n)
+                .map(|j| {
+                    let j = j as f64;
+                    let p = 2.0 - alpha;
+                    (j + 0.5) * b[j as usize] - (1.0 - alpha) / p * ((j + 1.0).powf(p) - j.powf(p))
+                })
+                .collect(),
+        };
+        CaputoWeights { scheme, b, c }
+    }
+
+    // Coefficient of u_step in d * D^alpha u(t_step)
+    pub fn lead(&self, step: usize) -> f64 {
+        match self.scheme {
+            TimeScheme::L1_2 if step > 1 => 1.0 + self.c[0],
+            _ => 1.0,
+        }
+    }
+
+    // The part of d * D^alpha u(t_step) carried over from earlier levels, with the sign chosen
+    // so that d * D^alpha u(t_step) = lead * u_step - known. `u(k)` is the value at level k < step.
+    pub fn known(&self, step: usize, u: impl Fn(usize) -> f64) -> f64 {
+        let b = &self.b;
+        if self.scheme == TimeScheme::L1 || step == 1 {
+            // Sum_{j=1}^{step-1} (b_{j-1} - b_j) * u_{step-j} + b_{step-1} * u_0
+            let mut history = 0.0;
+            if step > 1 {
+                for k in 1..step {
+                    history += (b[k - 1] - b[k]) * u(step - k);
+                }
+            }
+            return history + b[step - 1] * u(0);
+        }
+        // Every interval before the newest one: b_(step-k) * delta_k plus the quadratic correction
+        // c_(step-k) * (delta_k - delta_(k-1)), with the first interval linear
+        let c = &self.c;
+        let mut history = b[step - 1] * (u(1) - u(0));
+        for k in 2..step {
+            let (u0, u1, u2) = (u(k - 2), u(k - 1), u(k));
+            history += b[step - k] * (u2 - u1) + c[step - k] * (u2 - 2.0 * u1 + u0);
+        }
+        let (prev, prev2) = (u(step - 1), u(step - 2));
+        (1.0 + c[0]) * prev + c[0] * (prev - prev2) - history
+    }
+}
+
+// Time-to-expiry nodes 0 = tau_0 < tau_1 < ... < tau_N = T. Graded meshes tau_j = T (j/N)^r with
+// r > 1 cluster the steps at expiry, where the payoff kink and the weak t^alpha singularity of the
+// Caputo solution sit; r = (2 - alpha) / alpha restores the full 2 - alpha order of L1 (Stynes et al.
+// 2017). Custom takes the nodes as given, so N is its length minus one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimeMesh { Uniform, Graded { r: f64 }, Custom(Vec<f64>) }
+
+impl TimeMesh {
+    // Custom meshes are taken as they are; the solver's validate checks them before calling this
+    pub(crate) fn nodes(&self, t: f64, n: usize) -> Vec<f64> {
+        match self {
+            TimeMesh::Uniform => {
+                let dt = t / n as f64;
+                (0..=n).map(|j| j as f64 * dt).collect()
+            }
+            TimeMesh::Graded { r } => (0..=n).map(|j| t * (j as f64 / n as f64).powf(*r)).collect(),
+            TimeMesh::Custom(nodes) => nodes.clone(),
+        }
+    }
+
+    pub fn is_uniform(&self) -> bool {
+        matches!(self, TimeMesh::Uniform)
+    }
+}
+
+// L1 on a non-uniform mesh. With tau_k = t_k - t_(k-1), the interval k contributes
+// ((t_n - t_(k-1))^(1-alpha) - (t_n - t_k)^(1-alpha)) / (Gamma(2-alpha) tau_k) * (u_k - u_(k-1)).
+// Scaling by the local d_n = Gamma(2-alpha) tau_n^alpha makes the newest weight 1, so the implicit
+// step looks like the uniform one with d replaced by d_n; the weights change with every step.
+pub struct NonUniformL1 {
+    // w[k] for k = 1..=step, w[step] = 1
+    pub w: Vec<f64>,
+}
+
+impl NonUniformL1 {
+    pub fn new(alpha: f64, times: &[f64], step: usize) -> Self {
+        let t_n = times[step];
+        let tau_n = t_n - times[step - 1];
+        let mut w = vec![0.0; step + 1];
+        for k in 1..step {
+            let tau_k = times[k] - times[k - 1];
+            let integral = (t_n - times[k - 1]).powf(1.0 - alpha) - (t_n - times[k]).powf(1.0 - alpha);
+            w[k] = tau_n.powf(alpha) * integral / tau_k;
+        }
+        // Set directly for the same 0^0 reason as b_0 in l1_weights
+        w[step] = 1.0;
+        NonUniformL1 { w }
+    }
+
+    // Counterpart of CaputoWeights::known with lead 1: u_(step-1) - Sum_{k<step} w_k (u_k - u_(k-1))
+    pub fn known(&self, u: impl Fn(usize) -> f64) -> f64 {
+        let step = self.w.len() - 1;
+        let mut history = u(step - 1);
+        for k in 1..step {
+            history -= self.w[k] * (u(k) - u(k - 1));
+        }
+        history
+    }
+}
+
+// Weights b_j = (j+1)^(1-alpha) - j^(1-alpha). b_0 = 1 is set explicitly because powf gives
+// 0^0 = 1, which would zero it at alpha = 1 (the Garman-Kohlhagen limit)
+pub fn l1_weights(alpha: f64, n: usize) -> Vec<f64> {
+    let mut b: Vec<f64> = (0..=n).map(|j| (j as f64 + 1.0).powf(1.0 - alpha) - (j as f64).powf(1.0 - alpha)).collect();
+    b[0] = 1.0;
+    b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use statrs::function::gamma::gamma;
+
+    // Error of the scheme's Caputo derivative of u(t) = t^3 at t = 1, whose exact value is
+    // Gamma(4) / Gamma(4 - alpha)
+    fn derivative_error(scheme: TimeScheme, alpha: f64, n: usize) -> f64 {
+        let dt = 1.0 / n as f64;
+        let u = |k: usize| (k as f64 * dt).powi(3);
+        let w = CaputoWeights::new(scheme, alpha, n);
+        let approx = (w.lead(n) * u(n) - w.known(n, u)) / (dt.powf(alpha) * gamma(2.0 - alpha));
+        (approx - gamma(4.0) / gamma(4.0 - alpha)).abs()
+    }
+
+    // E_alpha(-t^alpha) at t = 1 from its power series, the solution of D^alpha u = -u, u(0) = 1
+    fn mittag_leffler_at_one(alpha: f64) -> f64 {
+        (0..150).map(|k| (-1.0f64).powi(k) / gamma(alpha * k as f64 + 1.0)).sum()
+    }
+
+    // Implicit L1 for D^alpha u = -u on the given mesh, returning u(T)
+    fn relaxation_error(alpha: f64, times: &[f64]) -> f64 {
+        let mut u = vec![1.0];
+        for step in 1..times.len() {
+            let w = NonUniformL1::new(alpha, times, step);
+            let d = gamma(2.0 - alpha) * (times[step] - times[step - 1]).powf(alpha);
+            u.push(w.known(|k| u[k]) / (1.0 + d));
+        }
+        (u[times.len() - 1] - mittag_leffler_at_one(alpha)).abs()
+    }
+
+    #[test]
+    fn l1_2_converges_one_order_faster_than_l1() {
+        for alpha in [0.3, 0.6, 0.85] {
+            for (scheme, order) in [(TimeScheme::L1, 2.0 - alpha), (TimeScheme::L1_2, 3.0 - alpha)] {
+                let errors: Vec<f64> = [40, 80, 160, 320].iter().map(|&n| derivative_error(scheme, alpha, n)).collect();
+                let observed = (errors[2] / errors[3]).log2();
+                assert!((observed - order).abs() < 0.1, "{scheme:?} alpha {alpha}: order {observed}, expected {order}");
+            }
+        }
+    }
+
+    #[test]
+    fn nonuniform_l1_on_uniform_mesh_matches_l1() {
+        let alpha = 0.7;
+        let times = TimeMesh::Uniform.nodes(1.0, 50);
+        let uniform = CaputoWeights::new(TimeScheme::L1, alpha, 50);
+        let u = |k: usize| (1.0 + k as f64).ln();
+        for step in 1..=50 {
+            let diff = NonUniformL1::new(alpha, &times, step).known(u) - uniform.known(step, u);
+            assert!(diff.abs() < 1e-12, "step {step}: {diff}");
+        }
+    }
+
+    #[test]
+    fn graded_mesh_restores_l1_order() {
+        for alpha in [0.4, 0.6] {
+            let order = |mesh: &TimeMesh| {
+                let errors: Vec<f64> = [64, 128, 256].iter().map(|&n| relaxation_error(alpha, &mesh.nodes(1.0, n))).collect();
+                (errors[1] / errors[2]).log2()
+            };
+            let uniform = order(&TimeMesh::Uniform);
+            let graded = order(&TimeMesh::Graded { r: (2.0 - alpha) / alpha });
+            assert!((graded - (2.0 - alpha)).abs() < 0.15, "alpha {alpha}: graded order {graded}");
+            assert!(graded > uniform + 0.2, "alpha {alpha}: graded {graded} vs uniform {uniform}");
+        }
+    }
+}
@@ -0,0 +1,124 @@
+// Grid-convergence driver. The solver is run on levels that double both M and N, the empirical
+// order p comes from the last three prices, and Richardson extrapolation removes the leading
+// error term: P* = P_L + (P_L - P_(L-1)) / (2^p - 1). With M and N refined together p is the
+// smaller of the spatial order (2) and the time order (1 to 2 - alpha for L1, see caputo.rs).
+// Kinked payoffs need a grid with the strike on a node at every level (SpaceGrid::Sinh): on the
+// uniform grid the strike's position inside its cell changes from level to level and the
+// differences lose the consistent sign Richardson relies on.
+
+use std::fmt;
+
+use crate::caputo::TimeMesh;
+use crate::fractional_pde::{FxOptionParams, SolverConfig, SolverError, solve_fx_tfbs_with};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridLevel { pub m: usize, pub n: usize, pub price: f64 }
+
+#[derive(Clone, Debug)]
+pub struct ConvergenceReport {
+    pub levels: Vec<GridLevel>,
+    // Empirical order from the last three levels
+    pub order: f64,
+    // Signed discretisation error of the finest price, P* - P_L = (P_L - P_(L-1)) / (2^p - 1)
+    pub error_estimate: f64,
+    pub extrapolated: f64,
+    // Accuracy of the extrapolated price: its change from the extrapolation one level coarser when
+    // at least four levels ran, otherwise the finest-level error estimate
+    pub extrapolated_error: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConvergenceError {
+    // An order estimate needs at least three levels
+    TooFewLevels { levels: usize },
+    // A custom time mesh fixes N, so it cannot be refined
+    FixedTimeMesh,
+    // Successive differences do not shrink with a consistent sign, so the grids are not yet in the
+    // asymptotic range (or the problem has a non-smooth feature the grids keep resolving)
+    NotAsymptotic { differences: Vec<f64> },
+    // The solver produced a non-finite price on one of the grids
+    NonFinitePrice { m: usize, n: usize },
+    // The solver rejected the settings or failed on one of the grids
+    Solver { m: usize, n: usize, error: SolverError },
+}
+
+impl fmt::Display for ConvergenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvergenceError::TooFewLevels { levels } => write!(f, "{levels} levels given, at least 3 are needed"),
+            ConvergenceError::FixedTimeMesh => write!(f, "a custom time mesh cannot be refined"),
+            ConvergenceError::NotAsymptotic { differences } => {
+                write!(f, "price differences {differences:?} are not in the asymptotic range")
+            }
+            ConvergenceError::NonFinitePrice { m, n } => write!(f, "solver returned a non-finite price on the {m} x {n} grid"),
+            ConvergenceError::Solver { m, n, error } => write!(f, "solver failed on the {m} x {n} grid: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ConvergenceError {}
+
+// Prices at `spot` on `levels` grids starting from base.m x base.n, each twice as fine in both
+// directions as the one before. All other settings of `base` are kept.
+pub fn convergence_study(
+    params: &FxOptionParams, base: &SolverConfig, spot: f64, levels: usize,
+) -> Result<ConvergenceReport, ConvergenceError> {
+    if levels < 3 { return Err(ConvergenceError::TooFewLevels { levels }); }
+    if matches!(base.mesh, TimeMesh::Custom(_)) { return Err(ConvergenceError::FixedTimeMesh); }
+    let mut grid_levels = Vec::with_capacity(levels);
+    for level in 0..levels {
+        let (m, n) = (base.m << level, base.n << level);
+        let sol = solve_fx_tfbs_with(params, &SolverConfig { m, n, ..base.clone() })
+            .map_err(|error| ConvergenceError::Solver { m, n, error })?;
+        let price = sol.price_at(spot);
+        if !price.is_finite() { return Err(ConvergenceError::NonFinitePrice { m, n }); }
+        grid_levels.push(GridLevel { m, n, price });
+    }
+    let prices: Vec<f64> = grid_levels.iter().map(|l| l.price).collect();
+    let differences: Vec<f64> = prices.windows(2).map(|w| w[1] - w[0]).collect();
+    let not_asymptotic = || ConvergenceError::NotAsymptotic { differences: differences.clone() };
+
+    let last = levels - 1;
+    let (order, error_estimate, extrapolated) =
+        richardson(prices[last - 2], prices[last - 1], prices[last]).ok_or_else(not_asymptotic)?;
+    let extrapolated_error = if levels >= 4 {
+        let (_, _, coarser) = richardson(prices[last - 3], prices[last - 2], prices[last - 1]).ok_or_else(not_asymptotic)?;
+        (extrapolated - coarser).abs()
+    } else {
+        error_estimate.abs()
+    };
+    Ok(ConvergenceReport { levels: grid_levels, order, error_estimate, extrapolated, extrapolated_error })
+}
+
+// Order, error of p2 and extrapolated value from three prices on grids refined by 2, or None when
+// the differences change sign or do not shrink
+fn richardson(p0: f64, p1: f64, p2: f64) -> Option<(f64, f64, f64)> {
+    let (d1, d2) = (p1 - p0, p2 - p1);
+    if d2 == 0.0 { return Some((f64::INFINITY, 0.0, p2)); }
+    let ratio = d1 / d2;
+    if ratio <= 1.0 { return None; }
+    let order = ratio.log2();
+    let error = d2 / (ratio - 1.0);
+    Some((order, error, p2 + error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garman_kohlhagen::garman_kohlhagen;
+    use crate::grid::SpaceGrid;
+
+    #[test]
+    fn extrapolated_price_is_within_its_error_estimate_of_gk() {
+        let params = FxOptionParams { alpha: 1.0, ..FxOptionParams::eurusd_call() };
+        let base = SolverConfig { grid: SpaceGrid::Sinh { concentration: 0.1 }, ..SolverConfig::new(100, 50) };
+        let report = convergence_study(&params, &base, 1.10, 4).unwrap();
+        let gk = garman_kohlhagen(&params, 1.10).price;
+        let finest = report.levels[3].price;
+        // Implicit Euler in time limits the combined order to one
+        assert!((0.8..1.3).contains(&report.order), "order {}", report.order);
+        assert!((report.extrapolated - gk).abs() < (finest - gk).abs() / 4.0);
+        assert!((report.extrapolated - gk).abs() <= report.extrapolated_error, "{report:?} vs GK {gk}");
+        assert!((finest + report.error_estimate - report.extrapolated).abs() < 1e-15);
+    }
+}
@@ -0,0 +1,299 @@
+// Discount curves for the domestic and foreign currencies. A curve only has to supply discount
+// factors from today; zero and forward rates follow from them. InterpolatedCurve is log-linear in
+// the discount factor (piecewise-flat forwards) and is bootstrapped from deposit and OIS par rates,
+// either in code or from a CSV file with columns curve,instrument,maturity,rate.
+
+use std::fmt;
+use std::fs::File;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+pub trait DiscountCurve: fmt::Debug + Send + Sync {
+    // Discount factor from today to t (years)
+    fn discount_factor(&self, t: f64) -> f64;
+
+    // Continuously compounded zero rate to t
+    fn zero_rate(&self, t: f64) -> f64 {
+        if t <= 0.0 { return self.forward_rate(0.0, 1e-6); }
+        -self.discount_factor(t).ln() / t
+    }
+
+    // Continuously compounded forward rate between t1 and t2
+    fn forward_rate(&self, t1: f64, t2: f64) -> f64 {
+        (self.discount_factor(t1) / self.discount_factor(t2)).ln() / (t2 - t1)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FlatCurve { pub rate: f64 }
+
+impl DiscountCurve for FlatCurve {
+    fn discount_factor(&self, t: f64) -> f64 {
+        (-self.rate * t).exp()
+    }
+}
+
+// Parallel shift of a curve by a continuously compounded spread, used for rho bumps
+#[derive(Clone, Debug)]
+pub struct ShiftedCurve { pub base: Arc<dyn DiscountCurve>, pub shift: f64 }
+
+impl DiscountCurve for ShiftedCurve {
+    fn discount_factor(&self, t: f64) -> f64 {
+        self.base.discount_factor(t) * (-self.shift * t).exp()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct InterpolatedCurve {
+    // Pillar times (starting at 0) and the log discount factors there
+    times: Vec<f64>,
+    log_dfs: Vec<f64>,
+}
+
+impl InterpolatedCurve {
+    pub fn from_discount_factors(pillars: &[(f64, f64)]) -> Self {
+        let mut times = vec![0.0];
+        let mut log_dfs = vec![0.0];
+        for &(t, df) in pillars {
+            if t > *times.last().unwrap() {
+                times.push(t);
+                log_dfs.push(df.ln());
+            }
+        }
+        InterpolatedCurve { times, log_dfs }
+    }
+}
+
+impl DiscountCurve for InterpolatedCurve {
+    // Log-linear between pillars; the last forward rate is extended flat beyond the final pillar
+    fn discount_factor(&self, t: f64) -> f64 {
+        let n = self.times.len();
+        if n == 1 { return 1.0; }
+        let j = self.times.partition_point(|&x| x <= t).clamp(1, n - 1);
+        let (t0, t1) = (self.times[j - 1], self.times[j]);
+        let slope = (self.log_dfs[j] - self.log_dfs[j - 1]) / (t1 - t0);
+        (self.log_dfs[j - 1] + slope * (t - t0)).exp()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InstrumentKind {
+    // Simple-interest deposit: DF(T) = 1 / (1 + r T)
+    Deposit,
+    // OIS par swap with annual fixed payments (short final period) against compounded overnight
+    Ois,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RateInstrument { pub kind: InstrumentKind, pub maturity: f64, pub rate: f64 }
+
+// Fails on the first instrument whose rate implies a discount factor that is not positive and finite
+pub fn bootstrap(instruments: &[RateInstrument]) -> Result<InterpolatedCurve, CurveError> {
+    let mut sorted = instruments.to_vec();
+    sorted.sort_by(|a, b| a.maturity.total_cmp(&b.maturity));
+    let mut pillars: Vec<(f64, f64)> = Vec::new();
+    for inst in sorted {
+        let (t, r) = (inst.maturity, inst.rate);
+        let df = if inst.kind == InstrumentKind::Deposit || t <= 1.0 {
+            1.0 / (1.0 + r * t)
+        } else {
+            // DF(T) = (1 - r * sum_{i<n} tau_i DF(t_i)) / (1 + r tau_n). Coupon dates past the last pillar
+            // are interpolated towards the unknown DF(T), so iterate to the fixed point
+            let mut ends: Vec<f64> = (1..).map(|i| i as f64).take_while(|&x| x < t - 1e-9).collect();
+            ends.push(t);
+            let periods: Vec<(f64, f64)> = ends.iter().scan(0.0, |start, &end| {
+                let period = (*start, end);
+                *start = end;
+                Some(period)
+            }).collect();
+            let (last_start, _) = periods[periods.len() - 1];
+            let (prev_t, prev_df) = pillars.last().copied().unwrap_or((0.0, 1.0));
+            let mut df_t = prev_df / (1.0 + r * (t - prev_t));
+            for _ in 0..100 {
+                let mut trial = pillars.clone();
+                trial.push((t, df_t));
+                let curve = InterpolatedCurve::from_discount_factors(&trial);
+                let annuity: f64 = periods[..periods.len() - 1].iter().map(|&(a, b)| (b - a) * curve.discount_factor(b)).sum();
+                let next = (1.0 - r * annuity) / (1.0 + r * (t - last_start));
+                let done = (next - df_t).abs() < 1e-15;
+                df_t = next;
+                if done { break; }
+            }
+            df_t
+        };
+        if !(df > 0.0 && df.is_finite()) {
+            return Err(CurveError::BadDiscountFactor { maturity: t, rate: r, discount_factor: df });
+        }
+        pillars.push((t, df));
+    }
+    Ok(InterpolatedCurve::from_discount_factors(&pillars))
+}
+
+#[derive(Debug)]
+pub enum CurveError {
+    Io(std::io::Error),
+    Csv(csv::Error),
+    BadInstrument { row: usize, reason: String },
+    NoInstruments { curve: String },
+    // The instrument maturing at `maturity` bootstraps to a discount factor that is not positive and finite
+    BadDiscountFactor { maturity: f64, rate: f64, discount_factor: f64 },
+}
+
+impl fmt::Display for CurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurveError::Io(e) => write!(f, "cannot open rate file: {e}"),
+            CurveError::Csv(e) => write!(f, "cannot parse rate file: {e}"),
+            CurveError::BadInstrument { row, reason } => write!(f, "rate row {row}: {reason}"),
+            CurveError::NoInstruments { curve } => write!(f, "no instruments for curve '{curve}'"),
+            CurveError::BadDiscountFactor { maturity, rate, discount_factor } => {
+                write!(f, "rate {rate} at maturity {maturity} gives discount factor {discount_factor}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CurveError {}
+
+#[derive(Debug, Deserialize)]
+struct InstrumentRow {
+    curve: String,
+    instrument: String,
+    maturity: f64,
+    rate: f64,
+}
+
+// Bootstraps the curve named `curve` (e.g. "USD" or "EUR") from the instruments in the file
+pub fn bootstrap_from_file(path: &str, curve: &str) -> Result<InterpolatedCurve, CurveError> {
+    let file = File::open(path).map_err(CurveError::Io)?;
+    let mut rdr = csv::Reader::from_reader(file);
+    let mut instruments = Vec::new();
+    for (row, record) in rdr.deserialize::<InstrumentRow>().enumerate() {
+        let record = record.map_err(CurveError::Csv)?;
+        if !record.curve.trim().eq_ignore_ascii_case(curve) { continue; }
+        let kind = match record.instrument.trim().to_ascii_lowercase().as_str() {
+            "deposit" => InstrumentKind::Deposit,
+            "ois" => InstrumentKind::Ois,
+            other => return Err(CurveError::BadInstrument { row, reason: format!("unknown instrument '{other}'") }),
+        };
+        if !(record.maturity > 0.0 && record.maturity.is_finite()) {
+            return Err(CurveError::BadInstrument { row, reason: "maturity must be positive".to_string() });
+        }
+        if !record.rate.is_finite() {
+            return Err(CurveError::BadInstrument { row, reason: "rate must be finite".to_string() });
+        }
+        instruments.push(RateInstrument { kind, maturity: record.maturity, rate: record.rate });
+    }
+    if instruments.is_empty() { return Err(CurveError::NoInstruments { curve: curve.to_string() }); }
+    bootstrap(&instruments)
+}
+
+// Domestic and foreign curves for a currency pair; when set on FxOptionParams they replace rd and rf
+#[derive(Clone, Debug)]
+pub struct RateCurves { pub domestic: Arc<dyn DiscountCurve>, pub foreign: Arc<dyn DiscountCurve> }
+
+impl RateCurves {
+    pub fn shifted(&self, domestic_shift: f64, foreign_shift: f64) -> Self {
+        RateCurves {
+            domestic: Arc::new(ShiftedCurve { base: self.domestic.clone(), shift: domestic_shift }),
+            foreign: Arc::new(ShiftedCurve { base: self.foreign.clone(), shift: foreign_shift }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractional_pde::{FxOptionParams, SolverConfig, solve_fx_tfbs_with};
+
+    fn instrument(kind: InstrumentKind, maturity: f64, rate: f64) -> RateInstrument {
+        RateInstrument { kind, maturity, rate }
+    }
+
+    // Rate the curve implies for the instrument: simple deposit rate, or the OIS par rate of annual
+    // fixed payments with a short final period
+    fn implied_rate(curve: &InterpolatedCurve, inst: RateInstrument) -> f64 {
+        let t = inst.maturity;
+        if inst.kind == InstrumentKind::Deposit || t <= 1.0 { return (1.0 / curve.discount_factor(t) - 1.0) / t; }
+        let mut ends: Vec<f64> = (1..).map(|i| i as f64).take_while(|&x| x < t - 1e-9).collect();
+        ends.push(t);
+        let annuity: f64 = ends.iter().scan(0.0, |start, &end| {
+            let accrual = (end - *start) * curve.discount_factor(end);
+            *start = end;
+            Some(accrual)
+        }).sum();
+        (1.0 - curve.discount_factor(t)) / annuity
+    }
+
+    #[test]
+    fn bootstrapped_curve_reprices_its_instruments() {
+        let instruments = [
+            instrument(InstrumentKind::Deposit, 0.25, 0.051),
+            instrument(InstrumentKind::Deposit, 0.5, 0.050),
+            instrument(InstrumentKind::Ois, 1.0, 0.047),
+            instrument(InstrumentKind::Ois, 2.0, 0.043),
+            instrument(InstrumentKind::Ois, 3.5, 0.040),
+            instrument(InstrumentKind::Ois, 5.0, 0.039),
+        ];
+        let curve = bootstrap(&instruments).unwrap();
+        for inst in instruments {
+            let model = implied_rate(&curve, inst);
+            assert!((model - inst.rate).abs() < 1e-12, "{:?} {}: {model} vs {}", inst.kind, inst.maturity, inst.rate);
+        }
+    }
+
+    #[test]
+    fn curves_bootstrapped_from_the_rate_file_reprice_their_rows() {
+        let text = std::fs::read_to_string("rate_curves.csv").unwrap();
+        for name in ["USD", "EUR"] {
+            let curve = bootstrap_from_file("rate_curves.csv", name).unwrap();
+            let rows: Vec<Vec<&str>> = text.lines().skip(1).map(|l| l.split(',').collect::<Vec<_>>()).filter(|r| r[0] == name).collect();
+            assert_eq!(rows.len(), 6);
+            for r in rows {
+                let kind = if r[1] == "deposit" { InstrumentKind::Deposit } else { InstrumentKind::Ois };
+                let inst = instrument(kind, r[2].parse().unwrap(), r[3].parse().unwrap());
+                let model = implied_rate(&curve, inst);
+                assert!((model - inst.rate).abs() < 1e-12, "{name} {:?} {}: {model} vs {}", inst.kind, inst.maturity, inst.rate);
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_rate_rows_are_rejected_with_their_row() {
+        let path = std::env::temp_dir().join(format!("bad_rates_{}.csv", std::process::id()));
+        let read = |row: &str, curve: &str| {
+            std::fs::write(&path, format!("curve,instrument,maturity,rate\nUSD,deposit,0.5,0.04\n{row}\n")).unwrap();
+            bootstrap_from_file(path.to_str().unwrap(), curve)
+        };
+        assert!(read("USD,ois,1.0,0.039", "USD").is_ok());
+        for row in ["USD,swap,1.0,0.039", "USD,ois,0.0,0.039", "USD,ois,-1.0,0.039", "USD,ois,NaN,0.039", "USD,ois,1.0,inf"] {
+            assert!(matches!(read(row, "USD"), Err(CurveError::BadInstrument { row: 1, .. })), "{row}");
+        }
+        // Rows of other curves are skipped, even malformed ones
+        assert!(matches!(read("EUR,swap,1.0,0.02", "GBP"), Err(CurveError::NoInstruments { .. })));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rates_giving_no_positive_discount_factor_are_rejected() {
+        let deposit = [instrument(InstrumentKind::Deposit, 1.0, -1.5)];
+        assert!(matches!(bootstrap(&deposit), Err(CurveError::BadDiscountFactor { maturity: 1.0, .. })));
+        let ois = [instrument(InstrumentKind::Deposit, 0.5, 0.05), instrument(InstrumentKind::Ois, 3.0, 2.0)];
+        assert!(matches!(bootstrap(&ois), Err(CurveError::BadDiscountFactor { maturity: 3.0, .. })));
+    }
+
+    #[test]
+    fn flat_curves_reproduce_the_flat_rate_solve() {
+        let params = FxOptionParams::eurusd_call();
+        let curves = RateCurves { domestic: Arc::new(FlatCurve { rate: params.rd }), foreign: Arc::new(FlatCurve { rate: params.rf }) };
+        let config = SolverConfig::new(200, 100);
+        let flat = solve_fx_tfbs_with(&params, &config).unwrap();
+        let curved = solve_fx_tfbs_with(&FxOptionParams { rate_curves: Some(curves), ..params }, &config).unwrap();
+        // Curve discount factors go through exp and ln, so agreement is to rounding, not bit for bit
+        let at_spot = (flat.price_at(1.10) - curved.price_at(1.10)).abs();
+        assert!(at_spot < 1e-15, "price difference at spot {at_spot}");
+        let max_diff = flat.prices.iter().zip(&curved.prices).map(|(a, b)| (a - b).abs() / a.abs().max(1.0)).fold(0.0, f64::max);
+        assert!(max_diff < 1e-14, "largest relative price difference {max_diff}");
+    }
+}
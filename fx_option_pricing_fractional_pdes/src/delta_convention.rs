@@ -0,0 +1,192 @@
+// Strike <-> delta conversions for the FX quoting conventions: spot or forward delta, each with
+// or without premium adjustment. With V the premium in domestic per unit of foreign and Delta_S
+// the spot delta dV/dS:
+//   spot              Delta_S
+//   forward           Delta_S / DF_f(T)
+//   spot, PA          Delta_S - V / S
+//   forward, PA       (Delta_S - V / S) / DF_f(T)
+// Deltas can come from closed-form Garman-Kohlhagen or from the fractional solver's numerical
+// delta. Premium-adjusted call deltas are not monotone in strike; the conventional strike is the
+// one above the delta peak, which is the largest root.
+
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use crate::calibration::SmileQuote;
+use crate::fractional_pde::{FxOptionParams, SolverConfig, SolverError, solve_fx_tfbs_with};
+use crate::garman_kohlhagen::garman_kohlhagen;
+use crate::implied::{ImpliedError, find_roots};
+use crate::payoff::OptionType;
+
+// Strike search covers the forward +/- this many standard deviations (in log-strike)
+const STRIKE_SEARCH_STDEVS: f64 = 6.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeltaConvention { Spot, Forward, SpotPremiumAdjusted, ForwardPremiumAdjusted }
+
+impl DeltaConvention {
+    pub fn is_premium_adjusted(&self) -> bool {
+        matches!(self, DeltaConvention::SpotPremiumAdjusted | DeltaConvention::ForwardPremiumAdjusted)
+    }
+
+    // Expresses a spot delta and premium at `spot` in this convention
+    pub fn from_spot_delta(&self, spot_delta: f64, premium: f64, spot: f64, params: &FxOptionParams) -> f64 {
+        let (_, df_f) = params.discount_factors(0.0, params.t);
+        match self {
+            DeltaConvention::Spot => spot_delta,
+            DeltaConvention::Forward => spot_delta / df_f,
+            DeltaConvention::SpotPremiumAdjusted => spot_delta - premium / spot,
+            DeltaConvention::ForwardPremiumAdjusted => (spot_delta - premium / spot) / df_f,
+        }
+    }
+}
+
+pub fn forward(params: &FxOptionParams, spot: f64) -> f64 {
+    let (df_d, df_f) = params.discount_factors(0.0, params.t);
+    spot * df_f / df_d
+}
+
+pub fn gk_delta(params: &FxOptionParams, spot: f64, convention: DeltaConvention) -> f64 {
+    let gk = garman_kohlhagen(params, spot);
+    convention.from_spot_delta(gk.delta, gk.price, spot, params)
+}
+
+pub fn fractional_delta(
+    params: &FxOptionParams, config: &SolverConfig, spot: f64, convention: DeltaConvention,
+) -> Result<f64, SolverError> {
+    let sol = solve_fx_tfbs_with(params, config)?;
+    Ok(convention.from_spot_delta(sol.delta_at(spot), sol.price_at(spot), spot, params))
+}
+
+// Strike of the option in `params` whose Garman-Kohlhagen delta is `delta` (signed: puts negative).
+// Unadjusted vanilla deltas invert in closed form; everything else needs a root search.
+pub fn gk_strike_from_delta(params: &FxOptionParams, spot: f64, delta: f64, convention: DeltaConvention) -> Result<f64, ImpliedError> {
+    let vanilla = matches!(params.option_type, OptionType::Call | OptionType::Put);
+    if vanilla && !convention.is_premium_adjusted() {
+        let phi = if params.option_type == OptionType::Call { 1.0 } else { -1.0 };
+        let df = if convention == DeltaConvention::Spot { params.discount_factors(0.0, params.t).1 } else { 1.0 };
+        let target = phi * delta / df;
+        if !(0.0 < target && target < 1.0) {
+            return Err(ImpliedError::NoSolution { target: delta, min_value: phi.min(0.0) * df, max_value: phi.max(0.0) * df });
+        }
+        let vol_t = params.sigma * params.t.sqrt();
+        let d1 = phi * Normal::new(0.0, 1.0).unwrap().inverse_cdf(target);
+        return Ok(forward(params, spot) * (-d1 * vol_t + 0.5 * vol_t * vol_t).exp());
+    }
+    strike_search(params, spot, delta, |k| Ok(gk_delta(&FxOptionParams { k, ..params.clone() }, spot, convention)))
+}
+
+// Strike whose delta under the fractional model (numerical delta from the solver) is `delta`
+pub fn fractional_strike_from_delta(
+    params: &FxOptionParams, config: &SolverConfig, spot: f64, delta: f64, convention: DeltaConvention,
+) -> Result<f64, ImpliedError> {
+    strike_search(params, spot, delta, |k| fractional_delta(&FxOptionParams { k, ..params.clone() }, config, spot, convention))
+}
+
+fn strike_search(
+    params: &FxOptionParams, spot: f64, delta: f64, delta_at: impl Fn(f64) -> Result<f64, SolverError>,
+) -> Result<f64, ImpliedError> {
+    let centre = forward(params, spot).ln();
+    let width = STRIKE_SEARCH_STDEVS * params.sigma * params.t.sqrt();
+    let roots = find_roots(|log_k| delta_at(log_k.exp()), delta, (centre - width, centre + width))?;
+    // Largest root: the conventional branch for premium-adjusted calls, the only root otherwise
+    Ok(roots[roots.len() - 1].exp())
+}
+
+// Wing vols from a delta risk reversal and (smile) butterfly: call = ATM + BF + RR/2, put = ATM + BF - RR/2
+pub fn wing_vols(atm_vol: f64, risk_reversal: f64, butterfly: f64) -> (f64, f64) {
+    (atm_vol + butterfly + 0.5 * risk_reversal, atm_vol + butterfly - 0.5 * risk_reversal)
+}
+
+// Turns one expiry of ATM / RR / BF quotes into strike quotes the calibrator and pricer accept.
+// The ATM quote is taken at the forward; each (delta, rr, bf) pillar yields a call at +delta and a
+// put at -delta, with strikes from Garman-Kohlhagen at their own wing vols as the market does.
+pub fn smile_quotes_from_rr_bf(
+    base: &FxOptionParams, spot: f64, t: f64, atm_vol: f64, pillars: &[(f64, f64, f64)], convention: DeltaConvention,
+) -> Result<Vec<SmileQuote>, ImpliedError> {
+    let at_vol = |option_type: OptionType, sigma: f64, k: f64| FxOptionParams { t, k, sigma, option_type, ..base.clone() };
+    let atm_k = forward(&FxOptionParams { t, ..base.clone() }, spot);
+    let mut quotes = vec![SmileQuote {
+        t, k: atm_k, option_type: OptionType::Call, premium: garman_kohlhagen(&at_vol(OptionType::Call, atm_vol, atm_k), spot).price,
+    }];
+    for &(delta, rr, bf) in pillars {
+        let (call_vol, put_vol) = wing_vols(atm_vol, rr, bf);
+        for (option_type, vol, signed_delta) in [(OptionType::Put, put_vol, -delta), (OptionType::Call, call_vol, delta)] {
+            let k = gk_strike_from_delta(&at_vol(option_type, vol, atm_k), spot, signed_delta, convention)?;
+            let premium = garman_kohlhagen(&at_vol(option_type, vol, k), spot).price;
+            quotes.push(SmileQuote { t, k, option_type, premium });
+        }
+    }
+    quotes.sort_by(|a, b| a.k.total_cmp(&b.k));
+    Ok(quotes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONVENTIONS: [DeltaConvention; 4] = [
+        DeltaConvention::Spot, DeltaConvention::Forward, DeltaConvention::SpotPremiumAdjusted, DeltaConvention::ForwardPremiumAdjusted,
+    ];
+
+    #[test]
+    fn strikes_round_trip_through_delta_in_every_convention() {
+        // The fractional search runs on a coarse grid and in the forward premium-adjusted convention
+        // only: it shares the strike search with the others and the round trip is exact on any grid
+        let config = SolverConfig::new(100, 50);
+        for convention in CONVENTIONS {
+            for (option_type, delta) in [(OptionType::Call, 0.25), (OptionType::Put, -0.25), (OptionType::Call, 0.10)] {
+                let params = FxOptionParams { option_type, ..FxOptionParams::eurusd_call() };
+                let k = gk_strike_from_delta(&params, 1.10, delta, convention).unwrap();
+                let back = gk_delta(&FxOptionParams { k, ..params.clone() }, 1.10, convention);
+                assert!((back - delta).abs() < 1e-6, "{convention:?} {option_type:?}: delta {back} at K {k}, wanted {delta}");
+
+                if convention != DeltaConvention::ForwardPremiumAdjusted { continue; }
+                let k = fractional_strike_from_delta(&params, &config, 1.10, delta, convention).unwrap();
+                let back = fractional_delta(&FxOptionParams { k, ..params.clone() }, &config, 1.10, convention).unwrap();
+                assert!((back - delta).abs() < 1e-6, "{convention:?} {option_type:?}: fractional delta {back} at K {k}, wanted {delta}");
+            }
+        }
+    }
+
+    #[test]
+    fn smile_quotes_hit_their_deltas_at_their_wing_vols() {
+        let (base, t, atm_vol) = (FxOptionParams::eurusd_call(), 0.5, 0.15);
+        let pillars = [(0.25, 0.010, 0.003), (0.10, 0.020, 0.009)];
+        for convention in CONVENTIONS {
+            let quotes = smile_quotes_from_rr_bf(&base, 1.10, t, atm_vol, &pillars, convention).unwrap();
+            assert_eq!(quotes.len(), 5);
+            assert!(quotes.windows(2).all(|q| q[0].k < q[1].k), "{convention:?}: strikes not sorted");
+            // Sorted by strike: 10 and 25 delta puts, ATM, 25 and 10 delta calls
+            let atm = quotes[2];
+            assert_eq!((atm.option_type, atm.k), (OptionType::Call, forward(&FxOptionParams { t, ..base.clone() }, 1.10)));
+            let wings = [(0, OptionType::Put, 1), (1, OptionType::Put, 0), (3, OptionType::Call, 0), (4, OptionType::Call, 1)];
+            for (i, option_type, pillar) in wings {
+                let (delta, rr, bf) = pillars[pillar];
+                let (call_vol, put_vol) = wing_vols(atm_vol, rr, bf);
+                let (vol, signed_delta) = if option_type == OptionType::Call { (call_vol, delta) } else { (put_vol, -delta) };
+                let q = quotes[i];
+                let params = FxOptionParams { t, k: q.k, sigma: vol, option_type, ..base.clone() };
+                assert_eq!(q.option_type, option_type);
+                assert!((gk_delta(&params, 1.10, convention) - signed_delta).abs() < 1e-6, "{convention:?} {option_type:?} {delta}");
+                assert!((q.premium - garman_kohlhagen(&params, 1.10).price).abs() < 1e-15);
+            }
+        }
+    }
+
+    #[test]
+    fn premium_adjusted_call_strike_lies_above_the_delta_peak() {
+        // The premium-adjusted call delta rises from 0 at K = 0 to a peak and falls again, so 0.25 is
+        // hit twice; the quoted strike is the out-of-the-money one
+        let params = FxOptionParams::eurusd_call();
+        for convention in [DeltaConvention::SpotPremiumAdjusted, DeltaConvention::ForwardPremiumAdjusted] {
+            let delta_at = |k: f64| gk_delta(&FxOptionParams { k, ..params.clone() }, 1.10, convention);
+            let strikes: Vec<f64> = (1..400).map(|i| 0.005 * i as f64).collect();
+            let peak = strikes.iter().copied().max_by(|a, b| delta_at(*a).total_cmp(&delta_at(*b))).unwrap();
+            assert!(strikes.iter().any(|&k| k < peak && delta_at(k) < 0.25), "{convention:?}: no lower branch");
+
+            let k = gk_strike_from_delta(&params, 1.10, 0.25, convention).unwrap();
+            assert!(k > peak, "{convention:?}: strike {k} below the delta peak at {peak}");
+            assert!((delta_at(k) - 0.25).abs() < 1e-6);
+        }
+    }
+}
@@ -1,75 +1,810 @@
+use std::fmt;
+
 use faer::{Mat, prelude::*};
 use statrs::function::gamma::gamma;
 
+use crate::alpha_schedule::AlphaSchedule;
+use crate::caputo::{CaputoWeights, NonUniformL1, TimeMesh, TimeScheme};
+use crate::curves::RateCurves;
+use crate::greeks::grid_delta_gamma;
+use crate::grid::{Domain, SpaceGrid};
+use crate::interpolation::pchip_log;
+use crate::memory::{MemoryMode, SoeHistory, SoeKernel, Storage};
+use crate::payoff::{Barrier, BoundaryCondition, ExerciseStyle, OptionType, PayoffSmoothing};
+use crate::tridiagonal::{LinearSolver, TridiagonalMatrix};
+
+#[derive(Clone, Debug)]
 pub struct FxOptionParams {
     pub s_max: f64, pub k: f64, pub t: f64, pub rd: f64, 
-    pub rf: f64, pub sigma: f64, pub alpha: f64,
+    pub rf: f64, pub sigma: f64, pub alpha: f64, pub option_type: OptionType,
+    pub exercise: ExerciseStyle, pub barrier: Option<Barrier>,
+    // Term structures for both currencies; when set they replace the flat rd and rf
+    pub rate_curves: Option<RateCurves>,
+    // Variable-order alpha(t); when set it replaces the constant alpha. Needs the exact memory mode
+    pub alpha_schedule: Option<AlphaSchedule>,
+}
+
+impl FxOptionParams {
+    // 1y EURUSD call struck at 1.10 with alpha 0.85 (rd 4%, rf 2%, vol 15%, s_max 20): the example
+    // in main and the base case of the tests and benches, which vary it with struct update syntax
+    pub fn eurusd_call() -> Self {
+        FxOptionParams {
+            s_max: 20.0, k: 1.10, t: 1.0, rd: 0.04, rf: 0.02, sigma: 0.15, alpha: 0.85, option_type: OptionType::Call,
+            exercise: ExerciseStyle::European, barrier: None, rate_curves: None, alpha_schedule: None,
+        }
+    }
+
+    // Domestic and foreign discount factors from calendar time t1 to t2 (years from today)
+    pub fn discount_factors(&self, t1: f64, t2: f64) -> (f64, f64) {
+        match &self.rate_curves {
+            Some(c) => (
+                c.domestic.discount_factor(t2) / c.domestic.discount_factor(t1),
+                c.foreign.discount_factor(t2) / c.foreign.discount_factor(t1),
+            ),
+            None => ((-self.rd * (t2 - t1)).exp(), (-self.rf * (t2 - t1)).exp()),
+        }
+    }
+
+    // Continuously compounded domestic and foreign forward rates over [t1, t2]; rd and rf themselves
+    // for flat rates, so steps rebuilt at the same rates get the same matrix
+    pub fn forward_rates(&self, t1: f64, t2: f64) -> (f64, f64) {
+        if self.rate_curves.is_none() { return (self.rd, self.rf); }
+        let (df_d, df_f) = self.discount_factors(t1, t2);
+        (-df_d.ln() / (t2 - t1), -df_f.ln() / (t2 - t1))
+    }
+
+    // Zero rates to expiry; rd and rf themselves for flat rates
+    pub fn effective_rates(&self) -> (f64, f64) {
+        if self.rate_curves.is_none() { return (self.rd, self.rf); }
+        self.forward_rates(0.0, self.t)
+    }
+
+    // Fractional order at calendar time t
+    pub fn alpha_at(&self, t: f64) -> f64 {
+        self.alpha_schedule.as_ref().map_or(self.alpha, |s| s.alpha_at(t))
+    }
+
+    // alpha (or the whole schedule) moved by h, used for the alpha sensitivity
+    pub fn with_alpha_shift(&self, h: f64) -> Self {
+        FxOptionParams {
+            alpha: (self.alpha + h).min(1.0),
+            alpha_schedule: self.alpha_schedule.as_ref().map(|s| s.shifted(h)),
+            ..self.clone()
+        }
+    }
+
+    // Parallel shift of both rate inputs (flat rates or curves), used for rho
+    pub fn with_rate_shift(&self, domestic: f64, foreign: f64) -> Self {
+        FxOptionParams {
+            rd: self.rd + domestic,
+            rf: self.rf + foreign,
+            rate_curves: self.rate_curves.as_ref().map(|c| c.shifted(domestic, foreign)),
+            ..self.clone()
+        }
+    }
+}
+
+pub struct FxPdeSolution {
+    pub s_grid: Vec<f64>,
+    pub prices: Vec<f64>,
+    // Time to expiry of each stored time level and the matching value surface on s_grid x times:
+    // every level, (M+1) x (N+1), unless the config keeps only slices
+    pub times: Vec<f64>,
+    pub surface: Mat<f64>,
+    // Early-exercise spot for each time level (index = step, 0 is expiry). Empty for European
+    // exercise; None where no node is in the exercise region.
+    pub exercise_boundary: Vec<Option<f64>>,
+}
+
+impl FxPdeSolution {
+    // Today's value at an arbitrary spot, monotone-cubic in log-spot between grid nodes
+    pub fn price_at(&self, spot: f64) -> f64 {
+        pchip_log(&self.s_grid, &self.prices, spot)
+    }
+
+    // dV/dS today at an arbitrary spot: three-point grid delta, interpolated like price_at
+    pub fn delta_at(&self, spot: f64) -> f64 {
+        let (delta, _) = grid_delta_gamma(&self.s_grid, &self.prices);
+        pchip_log(&self.s_grid, &delta, spot)
+    }
+
+    // Value at spot with tau years to expiry: cubic in log-spot on the two bracketing
+    // time levels, linear in time between them
+    pub fn value_at(&self, spot: f64, tau: f64) -> f64 {
+        let n = self.times.len() - 1;
+        let j = self.times.partition_point(|&t| t <= tau).clamp(1, n);
+        let (t0, t1) = (self.times[j - 1], self.times[j]);
+        let w = ((tau - t0) / (t1 - t0)).clamp(0.0, 1.0);
+        let v0 = pchip_log(&self.s_grid, self.column(j - 1), spot);
+        let v1 = pchip_log(&self.s_grid, self.column(j), spot);
+        v0 + w * (v1 - v0)
+    }
+
+    fn column(&self, step: usize) -> &[f64] {
+        self.surface.col_as_slice(step)
+    }
+}
+
+// Numerical settings for a solve: M spatial steps, N time steps and their placement, the spatial
+// domain and its boundary conditions, the payoff smoothing, the Caputo discretisation, how the
+// memory term is evaluated, which time levels are kept and which linear solver handles the
+// implicit step. `parallel` spreads
+// each step's history sums over threads when the crate is built with the `parallel` feature and is
+// ignored otherwise.
+#[derive(Clone, Debug)]
+pub struct SolverConfig {
+    pub m: usize, pub n: usize, pub domain: Domain, pub grid: SpaceGrid, pub mesh: TimeMesh,
+    pub lower_bc: BoundaryCondition, pub upper_bc: BoundaryCondition, pub smoothing: PayoffSmoothing,
+    pub scheme: TimeScheme, pub memory: MemoryMode, pub storage: Storage, pub linear_solver: LinearSolver,
+    pub parallel: bool,
+}
+
+impl SolverConfig {
+    pub fn new(m: usize, n: usize) -> Self {
+        SolverConfig {
+            m, n, domain: Domain::Fixed, grid: SpaceGrid::Uniform, mesh: TimeMesh::Uniform,
+            lower_bc: BoundaryCondition::Dirichlet, upper_bc: BoundaryCondition::Dirichlet,
+            smoothing: PayoffSmoothing::None, scheme: TimeScheme::L1, memory: MemoryMode::Exact, storage: Storage::Full,
+            linear_solver: LinearSolver::Tridiagonal, parallel: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolverError {
+    // A contract or grid input outside the range the scheme is defined on
+    InvalidParameter { name: &'static str, value: f64, reason: &'static str },
+    // Settings that are valid on their own but not together
+    UnsupportedCombination { reason: &'static str },
+    // The implicit step's matrix has a zero or non-finite pivot at `row` (interior node row + 1)
+    SingularMatrix { step: usize, row: usize },
+    // The solution at `node` went NaN or infinite at time level `step`
+    NonFiniteValue { step: usize, node: usize },
+    // The early-exercise problem at time level `step` was left with this residual
+    NotConverged { step: usize, residual: f64 },
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::InvalidParameter { name, value, reason } => write!(f, "invalid {name} = {value}: {reason}"),
+            SolverError::UnsupportedCombination { reason } => write!(f, "unsupported settings: {reason}"),
+            SolverError::SingularMatrix { step, row } => write!(f, "singular system at time step {step}, row {row}"),
+            SolverError::NonFiniteValue { step, node } => write!(f, "non-finite value at time step {step}, node {node}"),
+            SolverError::NotConverged { step, residual } => {
+                write!(f, "early-exercise problem unsolved at time step {step} (residual {residual:e})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+// Smallest run of interior nodes handed to one thread in parallel mode
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_NODES: usize = 32;
+
+// Largest complementarity residual of an American step, relative to the size of the solution,
+// accepted from the direct Brennan-Schwartz solve
+const LCP_TOL: f64 = 1e-9;
+
+pub fn solve_fx_tfbs_final_stable(params: FxOptionParams, m: usize, n: usize) -> Result<(Vec<f64>, Vec<f64>), SolverError> {
+    let sol = solve_fx_tfbs(&params, m, n)?;
+    Ok((sol.s_grid, sol.prices))
+}
+
+pub fn solve_fx_tfbs(params: &FxOptionParams, m: usize, n: usize) -> Result<FxPdeSolution, SolverError> {
+    solve_fx_tfbs_with(params, &SolverConfig::new(m, n))
+}
+
+pub fn solve_fx_tfbs_with(params: &FxOptionParams, config: &SolverConfig) -> Result<FxPdeSolution, SolverError> {
+    validate(params, config)?;
+    match params.barrier {
+        Some(barrier) if barrier.barrier_type.is_knock_in() => solve_knock_in(params, barrier, config),
+        _ => solve_grid(params, config),
+    }
+}
+
+// Everything solve_grid relies on without checking: enough nodes for the end rows, an order in
+// (0, 1] at every time level, a non-empty domain with the barrier inside it, and a combination
+// of settings the stepper implements
+pub(crate) fn validate(params: &FxOptionParams, config: &SolverConfig) -> Result<(), SolverError> {
+    let invalid = |name, value, reason| Err(SolverError::InvalidParameter { name, value, reason });
+    let unsupported = |reason| Err(SolverError::UnsupportedCombination { reason });
+    if config.m < 3 { return invalid("m", config.m as f64, "at least 3 spatial steps are needed"); }
+    if !(params.t > 0.0 && params.t.is_finite()) { return invalid("t", params.t, "expiry must be positive"); }
+    if !(params.k > 0.0 && params.k.is_finite()) { return invalid("k", params.k, "strike must be positive"); }
+    if !(params.sigma > 0.0 && params.sigma.is_finite()) { return invalid("sigma", params.sigma, "volatility must be positive"); }
+    if !params.rd.is_finite() { return invalid("rd", params.rd, "rate must be finite"); }
+    if !params.rf.is_finite() { return invalid("rf", params.rf, "rate must be finite"); }
+
+    match &config.mesh {
+        TimeMesh::Custom(nodes) => {
+            if nodes.len() < 2 || nodes[0] != 0.0 {
+                return invalid("mesh", nodes.len() as f64, "a custom time mesh starts at 0 and has at least one step");
+            }
+            if let Some(w) = nodes.windows(2).find(|w| w[1] <= w[0] || w[1].is_nan()) {
+                return invalid("mesh", w[1], "custom time mesh nodes must be strictly increasing");
+            }
+            let last = nodes[nodes.len() - 1];
+            if (last - params.t).abs() > 1e-12 * params.t { return invalid("mesh", last, "a custom time mesh must end at expiry"); }
+        }
+        _ if config.n == 0 => return invalid("n", 0.0, "at least one time step is needed"),
+        TimeMesh::Graded { r } if !(*r > 0.0 && r.is_finite()) => return invalid("r", *r, "grading exponent must be positive"),
+        _ => {}
+    }
+    if let Some(AlphaSchedule::PiecewiseConstant(pieces)) = &params.alpha_schedule {
+        if pieces.is_empty() { return invalid("schedule", 0.0, "a piecewise-constant schedule needs at least one piece"); }
+        if let Some(w) = pieces.windows(2).find(|w| w[1].0 <= w[0].0 || w[1].0.is_nan()) {
+            return invalid("schedule", w[1].0, "piece start times must be strictly increasing");
+        }
+    }
+    for tau in config.mesh.nodes(params.t, config.n) {
+        let alpha = params.alpha_at(params.t - tau);
+        if !(alpha > 0.0 && alpha <= 1.0) { return invalid("alpha", alpha, "fractional order must lie in (0, 1]"); }
+    }
+
+    match config.domain {
+        Domain::Fixed if !(params.s_max > params.k / 10.0 && params.s_max.is_finite()) => {
+            return invalid("s_max", params.s_max, "must exceed the lower grid edge k / 10");
+        }
+        Domain::StdDevs { spot, .. } if !(spot > 0.0 && spot.is_finite()) => return invalid("spot", spot, "spot must be positive"),
+        Domain::StdDevs { width, .. } if !(width > 0.0 && width.is_finite()) => {
+            return invalid("width", width, "domain width must be positive");
+        }
+        Domain::Range { s_min, .. } if !(s_min > 0.0 && s_min.is_finite()) => return invalid("s_min", s_min, "must be positive"),
+        Domain::Range { s_min, s_max } if !(s_max > s_min && s_max.is_finite()) => {
+            return invalid("s_max", s_max, "must exceed s_min");
+        }
+        _ => {}
+    }
+    if let SpaceGrid::Sinh { concentration } = config.grid && !(concentration > 0.0 && concentration.is_finite()) {
+        return invalid("concentration", concentration, "sinh concentration must be positive");
+    }
+    if let Some(b) = params.barrier {
+        let (x_min, x_max) = config.domain.bounds(params);
+        let x_b = b.level.ln();
+        if !(x_b > x_min && x_b < x_max) { return invalid("barrier", b.level, "barrier must lie inside the spatial domain"); }
+        if b.barrier_type.is_knock_in() && params.exercise != ExerciseStyle::European {
+            return unsupported("knock-in parity requires European exercise");
+        }
+        if b.barrier_type.is_knock_in() && b.rebate != 0.0 { return unsupported("knock-in parity carries no rebate"); }
+    }
+
+    if let MemoryMode::SumOfExponentials { tol } = config.memory {
+        if !(tol > 0.0 && tol.is_finite()) { return invalid("tol", tol, "sum-of-exponentials tolerance must be positive"); }
+        if params.alpha_schedule.is_some() { return unsupported("the sum-of-exponentials memory needs a constant alpha"); }
+        if config.scheme != TimeScheme::L1 { return unsupported("the sum-of-exponentials memory approximates the L1 weights"); }
+        if !config.mesh.is_uniform() { return unsupported("the sum-of-exponentials memory needs a uniform time mesh"); }
+    }
+    if let Storage::Slices(taus) = &config.storage {
+        if config.memory == MemoryMode::Exact { return unsupported("keeping only slices needs the sum-of-exponentials memory"); }
+        if let Some(&tau) = taus.iter().find(|&&tau| !(0.0..=params.t).contains(&tau)) {
+            return invalid("slice", tau, "slice times to expiry must lie in [0, t]");
+        }
+    }
+    if config.scheme != TimeScheme::L1 && !config.mesh.is_uniform() {
+        return unsupported("L1-2 is implemented on uniform time meshes only");
+    }
+    Ok(())
 }
 
-pub fn solve_fx_tfbs_final_stable(params: FxOptionParams, m: usize, n: usize) -> (Vec<f64>, Vec<f64>) {
-    let x_min = (params.k / 10.0).ln();
-    let x_max = params.s_max.ln();
+// In/out parity: KI = vanilla - KO (no rebate). On the knocked-in side of the barrier the
+// option is already vanilla; elsewhere the KO values are interpolated onto the vanilla grid.
+fn solve_knock_in(params: &FxOptionParams, barrier: Barrier, config: &SolverConfig) -> Result<FxPdeSolution, SolverError> {
+    let vanilla = solve_grid(&FxOptionParams { barrier: None, ..params.clone() }, config)?;
+    let ko_barrier = Barrier { barrier_type: barrier.barrier_type.knock_out(), level: barrier.level, rebate: 0.0 };
+    let ko = solve_grid(&FxOptionParams { barrier: Some(ko_barrier), ..params.clone() }, config)?;
+    let up = barrier.barrier_type.is_up();
+    let mut surface = vanilla.surface;
+    for step in 0..vanilla.times.len() {
+        for (i, &s) in vanilla.s_grid.iter().enumerate() {
+            let knocked = if up { s >= barrier.level } else { s <= barrier.level };
+            if !knocked { surface[(i, step)] -= pchip_log(&ko.s_grid, ko.surface.col_as_slice(step), s); }
+        }
+    }
+    let prices = surface.col_as_slice(vanilla.times.len() - 1).to_vec();
+    Ok(FxPdeSolution { s_grid: vanilla.s_grid, prices, times: vanilla.times, surface, exercise_boundary: Vec::new() })
+}
+
+fn solve_grid(params: &FxOptionParams, config: &SolverConfig) -> Result<FxPdeSolution, SolverError> {
+    let m = config.m;
+    // A custom mesh brings its own step count
+    let times = config.mesh.nodes(params.t, config.n);
+    let n = times.len() - 1;
+    let uniform = config.mesh.is_uniform();
+    // A knock-out truncates the grid so the barrier sits exactly on the boundary node
+    let (mut x_min, mut x_max) = config.domain.bounds(params);
+    let (mut lower_bc, mut upper_bc) = (config.lower_bc, config.upper_bc);
+    match params.barrier {
+        Some(b) if b.barrier_type.is_up() => (x_max, upper_bc) = (b.level.ln(), BoundaryCondition::Dirichlet),
+        Some(b) => (x_min, lower_bc) = (b.level.ln(), BoundaryCondition::Dirichlet),
+        None => {}
+    }
     let dx = (x_max - x_min) / m as f64;
     let dt = params.t / n as f64;
+    let step_size = |step: usize| if uniform { dt } else { times[step] - times[step - 1] };
     
-    let s_grid: Vec<f64> = (0..=m).map(|i| (x_min + i as f64 * dx).exp()).collect();
+    let (x_grid, s_grid) = config.grid.nodes(x_min, x_max, params.k, m);
 
     // PDE Coeffs
     let sigma2 = params.sigma.powi(2);
-    let drift = (params.rd - params.rf) - 0.5 * sigma2;
-    // Scale factor d = Gamma(2-alpha) * dt^alpha
-    let d = dt.powf(params.alpha) * gamma(2.0 - params.alpha);
+    // Variable order: alpha_n is evaluated at the calendar time of the new level, T - tau_step
+    let step_alpha = |step: usize| params.alpha_at(params.t - times[step]);
+    let alpha_1 = step_alpha(1);
+    // Scale factor d = Gamma(2-alpha) * dt^alpha, with the local step on a non-uniform mesh
+    let scale = |alpha: f64, step: usize| step_size(step).powf(alpha) * gamma(2.0 - alpha);
+    let mut weights = CaputoWeights::new(config.scheme, alpha_1, n);
 
-    // Weights b_j = (j+1)^(1-alpha) - j^(1-alpha)
-    let b: Vec<f64> = (0..=n).map(|j| (j as f64 + 1.0).powf(1.0 - params.alpha) - (j as f64).powf(1.0 - params.alpha)).collect();
+    // Every level with the full surface; a ring of the last two when only slices are kept, which is
+    // all the sum-of-exponentials history reads
+    let cols = match config.storage { Storage::Full => n + 1, Storage::Slices(_) => 2 };
+    let col = |step: usize| step % cols;
+    let mut v = Mat::<f64>::zeros(m + 1, cols);
+    for i in 0..=m {
+        v[(i, col(0))] = match config.smoothing {
+            PayoffSmoothing::None => params.option_type.payoff(s_grid[i], params.k),
+            PayoffSmoothing::CellAverage => {
+                let x_lo = if i == 0 { x_grid[0] } else { 0.5 * (x_grid[i - 1] + x_grid[i]) };
+                let x_hi = if i == m { x_grid[m] } else { 0.5 * (x_grid[i] + x_grid[i + 1]) };
+                params.option_type.cell_average(x_lo, x_hi, params.k)
+            }
+        };
+    }
+    match params.barrier {
+        Some(b) if b.barrier_type.is_up() => v[(m, col(0))] = b.rebate,
+        Some(b) => v[(0, col(0))] = b.rebate,
+        None => {}
+    }
 
-    let mut v = Mat::<f64>::zeros(m + 1, n + 1);
-    for i in 0..=m { v[(i, 0)] = (s_grid[i] - params.k).max(0.0); }
+    // Zero gamma extrapolates linearly in S from the two nodes next to each end
+    let rho_lower = (s_grid[1] - s_grid[0]) / (s_grid[2] - s_grid[1]);
+    let rho_upper = (s_grid[m] - s_grid[m - 1]) / (s_grid[m - 1] - s_grid[m - 2]);
 
-    // Discretization coefficients for matrix A
-    let alpha_coeff = d * (sigma2 / (2.0 * dx.powi(2)));
-    let beta_coeff = d * (drift / (2.0 * dx));
-    let gamma_coeff = d * params.rd;
+    // Discretization coefficients for matrix A at the short rates of a time step. A is tridiagonal,
+    // so only its three bands are stored; the dense copy is built on request
+    let american = params.exercise == ExerciseStyle::American;
+    let exercise_below = american && matches!(params.option_type, OptionType::Put | OptionType::DigitalPut);
+    let build_system = |rd: f64, rf: f64, d: f64, step: usize| {
+        let drift = (rd - rf) - 0.5 * sigma2;
+        let mut a_matrix = if config.grid.is_uniform() {
+            let alpha_coeff = d * (sigma2 / (2.0 * dx.powi(2)));
+            let beta_coeff = d * (drift / (2.0 * dx));
+            let gamma_coeff = d * rd;
 
-    let main_diag = 1.0 + 2.0 * alpha_coeff + gamma_coeff;
-    let upper_val = -(alpha_coeff + beta_coeff);
-    let lower_val = -(alpha_coeff - beta_coeff);
+            let main_diag = 1.0 + 2.0 * alpha_coeff + gamma_coeff;
+            let upper_val = -(alpha_coeff + beta_coeff);
+            let lower_val = -(alpha_coeff - beta_coeff);
 
-    let mut a_matrix = Mat::<f64>::zeros(m - 1, m - 1);
-    for i in 0..(m - 1) {
-        a_matrix[(i, i)] = main_diag;
-        if i > 0 { a_matrix[(i, i - 1)] = lower_val; }
-        if i < m - 2 { a_matrix[(i, i + 1)] = upper_val; }
-    }
-    let lu = a_matrix.partial_piv_lu();
+            TridiagonalMatrix::from_constant(m - 1, lower_val, main_diag, upper_val)
+        } else {
+            // Three-point V_x and V_xx on the spacings h- = x_i - x_(i-1), h+ = x_(i+1) - x_i, which
+            // reduce to the central differences above when h- = h+
+            let mut a_matrix = TridiagonalMatrix::from_constant(m - 1, 0.0, 0.0, 0.0);
+            for i in 1..m {
+                let (hm, hp) = (x_grid[i] - x_grid[i - 1], x_grid[i + 1] - x_grid[i]);
+                let diffusion = 0.5 * sigma2;
+                a_matrix.lower[i - 1] = -d * (diffusion * 2.0 - drift * hp) / (hm * (hm + hp));
+                a_matrix.diag[i - 1] = 1.0 + d * (diffusion * 2.0 - drift * (hp - hm)) / (hm * hp) + d * rd;
+                a_matrix.upper[i - 1] = -d * (diffusion * 2.0 + drift * hm) / (hp * (hm + hp));
+            }
+            a_matrix
+        };
+        // Neumann and Linearity ends substitute V_0 = V_1 - delta * (S_1 - S_0) or
+        // V_0 = (1 + rho) V_1 - rho V_2 (and the mirror images at S_M) into the end rows.
+        // lower[0] and upper[m-2] lie outside the band and keep the coupling to the boundary node.
+        match lower_bc {
+            BoundaryCondition::Dirichlet => {}
+            BoundaryCondition::Neumann => a_matrix.diag[0] += a_matrix.lower[0],
+            BoundaryCondition::Linearity => {
+                a_matrix.diag[0] += a_matrix.lower[0] * (1.0 + rho_lower);
+                a_matrix.upper[0] -= a_matrix.lower[0] * rho_lower;
+            }
+        }
+        match upper_bc {
+            BoundaryCondition::Dirichlet => {}
+            BoundaryCondition::Neumann => a_matrix.diag[m - 2] += a_matrix.upper[m - 2],
+            BoundaryCondition::Linearity => {
+                a_matrix.diag[m - 2] += a_matrix.upper[m - 2] * (1.0 + rho_upper);
+                a_matrix.lower[m - 2] -= a_matrix.upper[m - 2] * rho_upper;
+            }
+        }
+        // Thomas row i is interior node i + 1. American puts are eliminated from the top so that
+        // back substitution starts in their exercise region at the bottom of the grid.
+        let thomas = if exercise_below {
+            a_matrix.reversed().factorize().map_err(|row| SolverError::SingularMatrix { step, row: m - 1 - row })?
+        } else {
+            a_matrix.factorize().map_err(|row| SolverError::SingularMatrix { step, row: row + 1 })?
+        };
+        let dense_lu = match config.linear_solver {
+            LinearSolver::DenseLu => Some(a_matrix.to_dense().partial_piv_lu()),
+            LinearSolver::Tridiagonal => None,
+        };
+        Ok((a_matrix, thomas, dense_lu))
+    };
+    // Step `step` covers calendar time [T - tau_step, T - tau_(step-1)]; flat rates on a uniform mesh
+    // need one factorisation
+    let step_rates = |step: usize| params.forward_rates(params.t - times[step], params.t - times[step - 1]);
+    let (rd_1, rf_1) = step_rates(1);
+    let (mut a_matrix, mut thomas, mut dense_lu) = build_system(rd_1, rf_1, scale(alpha_1, 1), 1)?;
+    let intrinsic: Vec<f64> = s_grid.iter().map(|&s| params.option_type.payoff(s, params.k)).collect();
+    // Exercise floor of the interior unknowns in the order the projected solve sees them
+    let mut floor = intrinsic[1..m].to_vec();
+    if exercise_below { floor.reverse(); }
+    let mut soe = match config.memory {
+        MemoryMode::Exact => None,
+        MemoryMode::SumOfExponentials { tol } => Some(SoeHistory::new(SoeKernel::new(params.alpha, n, tol), m + 1)),
+    };
+    // Levels copied out in slice mode: the pair bracketing each requested time, as value_at reads them
+    let keep: Vec<usize> = match &config.storage {
+        Storage::Full => Vec::new(),
+        Storage::Slices(taus) => {
+            let mut keep = vec![0, n];
+            for &tau in taus {
+                let j = times.partition_point(|&t| t <= tau).clamp(1, n);
+                keep.extend([j - 1, j]);
+            }
+            keep.sort_unstable();
+            keep.dedup();
+            keep
+        }
+    };
+    let mut slices = Mat::<f64>::zeros(m + 1, keep.len());
+    let mut exercise_boundary = Vec::new();
+    let mut record = |v: &Mat<f64>, step: usize| {
+        if american { exercise_boundary.push(exercise_spot(params.option_type, &s_grid, &intrinsic, |i| v[(i, col(step))])); }
+        if let Ok(slot) = keep.binary_search(&step) {
+            for i in 0..=m { slices[(i, slot)] = v[(i, col(step))]; }
+        }
+    };
+    record(&v, 0);
 
     // Time Stepping
     for step in 1..=n {
-        let mut rhs = Mat::<f64>::zeros(m - 1, 1);
-        let t_curr = step as f64 * dt;
-        let v_upper = params.s_max * (-params.rf * t_curr).exp() - params.k * (-params.rd * t_curr).exp();
-
-        for i in 1..m {
-            // Correct L1 History: Sum_{j=1}^{step-1} (b_{j-1} - b_j) * V_{step-j} + b_{step-1} * V_0
-            let mut history = 0.0;
-            if step > 1 {
-                for k in 1..step {
-                    history += (b[k - 1] - b[k]) * v[(i, step - k)];
-                }
+        // The system is d * D^alpha V = d * L V divided through by the leading Caputo coefficient,
+        // which changes after the first step for L1-2
+        let varying = params.rate_curves.is_some() || params.alpha_schedule.is_some() || !uniform;
+        if step > 1 && (varying || weights.lead(step) != weights.lead(step - 1)) {
+            let (rd, rf) = step_rates(step);
+            let alpha = step_alpha(step);
+            if params.alpha_schedule.is_some() { weights = CaputoWeights::new(config.scheme, alpha, n); }
+            (a_matrix, thomas, dense_lu) = build_system(rd, rf, scale(alpha, step) / weights.lead(step), step)?;
+        }
+        let mut rhs = vec![0.0; m - 1];
+        let t_curr = times[step];
+        let (df_d, df_f) = params.discount_factors(params.t - t_curr, params.t);
+        let mut v_lower = params.option_type.lower_boundary(s_grid[0], params.k, df_d, df_f);
+        let mut v_upper = params.option_type.upper_boundary(s_grid[m], params.k, df_d, df_f);
+        match params.barrier {
+            Some(b) if b.barrier_type.is_up() => v_upper = b.rebate,
+            Some(b) => v_lower = b.rebate,
+            None => {}
+        }
+        if american {
+            v_lower = v_lower.max(intrinsic[0]);
+            v_upper = v_upper.max(intrinsic[m]);
+        }
+
+        if let Some(soe) = soe.as_mut() {
+            // Fast history: V_{step-1} - Sum_{j>=1} b_j (V_{step-j} - V_{step-j-1}) with b_j from the SOE kernel
+            for i in 1..m {
+                if step > 1 { soe.advance(i, v[(i, col(step - 1))] - v[(i, col(step - 2))]); }
+                rhs[i - 1] = v[(i, col(step - 1))] - soe.history(i);
+            }
+        } else if !uniform {
+            let row = NonUniformL1::new(step_alpha(step), &times, step);
+            fill_interior(&mut rhs, config.parallel, |i| row.known(|k| v[(i, col(k))]));
+        } else {
+            let lead = weights.lead(step);
+            fill_interior(&mut rhs, config.parallel, |i| weights.known(step, |k| v[(i, col(k))]) / lead);
+        }
+
+        // Apply boundary conditions to the first and last equations in the tridiagonal system
+        let lower_delta = params.option_type.lower_delta(df_f);
+        let upper_delta = params.option_type.upper_delta(df_f);
+        match lower_bc {
+            BoundaryCondition::Dirichlet => rhs[0] -= a_matrix.lower[0] * v_lower,
+            BoundaryCondition::Neumann => rhs[0] += a_matrix.lower[0] * lower_delta * (s_grid[1] - s_grid[0]),
+            BoundaryCondition::Linearity => {}
+        }
+        match upper_bc {
+            BoundaryCondition::Dirichlet => rhs[m - 2] -= a_matrix.upper[m - 2] * v_upper,
+            BoundaryCondition::Neumann => rhs[m - 2] -= a_matrix.upper[m - 2] * upper_delta * (s_grid[m] - s_grid[m - 1]),
+            BoundaryCondition::Linearity => {}
+        }
+
+        if american {
+            // Early exercise is the complementarity problem V >= payoff, A V >= rhs with one of them
+            // binding on every row. The exercise region of a call is a block at the top of the grid
+            // and that of a put one at the bottom, which makes the Brennan-Schwartz solve exact; the
+            // residual check catches the cases (a non-M-matrix A at extreme drift) where it is not.
+            let mut x = rhs.clone();
+            if exercise_below { x.reverse(); }
+            thomas.solve_projected_in_place(&mut x, &floor);
+            if exercise_below { x.reverse(); }
+            let residual = lcp_residual(&a_matrix, &rhs, &x, &intrinsic[1..m]);
+            let size = x.iter().fold(1.0, |acc: f64, xi| acc.max(xi.abs()));
+            if residual > LCP_TOL * size || residual.is_nan() { return Err(SolverError::NotConverged { step, residual }); }
+            for i in 1..m { v[(i, col(step))] = x[i - 1]; }
+        } else if let Some(lu) = dense_lu.as_ref() {
+            let sol = lu.solve(&Mat::<f64>::from_fn(m - 1, 1, |i, _| rhs[i]));
+            for i in 1..m { v[(i, col(step))] = sol[(i - 1, 0)]; }
+        } else {
+            thomas.solve_in_place(&mut rhs);
+            for i in 1..m { v[(i, col(step))] = rhs[i - 1]; }
+        }
+
+        // Recover the eliminated boundary values from the interior solution
+        match lower_bc {
+            BoundaryCondition::Dirichlet => {}
+            BoundaryCondition::Neumann => v_lower = v[(1, col(step))] - lower_delta * (s_grid[1] - s_grid[0]),
+            BoundaryCondition::Linearity => v_lower = (1.0 + rho_lower) * v[(1, col(step))] - rho_lower * v[(2, col(step))],
+        }
+        match upper_bc {
+            BoundaryCondition::Dirichlet => {}
+            BoundaryCondition::Neumann => v_upper = v[(m - 1, col(step))] + upper_delta * (s_grid[m] - s_grid[m - 1]),
+            BoundaryCondition::Linearity => v_upper = (1.0 + rho_upper) * v[(m - 1, col(step))] - rho_upper * v[(m - 2, col(step))],
+        }
+        if american {
+            v_lower = v_lower.max(intrinsic[0]);
+            v_upper = v_upper.max(intrinsic[m]);
+        }
+        v[(0, col(step))] = v_lower; // Left boundary S -> 0
+        v[(m, col(step))] = v_upper; // Right boundary S -> S_max
+        if let Some(node) = (0..=m).find(|&i| !v[(i, col(step))].is_finite()) {
+            return Err(SolverError::NonFiniteValue { step, node });
+        }
+        record(&v, step);
+    }
+
+    let prices = (0..=m).map(|i| v[(i, col(n))]).collect();
+    let (times, surface) = match config.storage {
+        Storage::Full => (times, v),
+        Storage::Slices(_) => (keep.iter().map(|&j| times[j]).collect(), slices),
+    };
+    Ok(FxPdeSolution { s_grid, prices, times, surface, exercise_boundary })
+}
+
+// rhs[i - 1] = node(i) for every interior node i. In parallel mode rayon takes runs of nodes, but
+// each node is still evaluated by the same code in the same order, so the result is bit-for-bit
+// the serial one. The sum-of-exponentials history is only a few terms per node and stays serial.
+fn fill_interior(rhs: &mut [f64], parallel: bool, node: impl Fn(usize) -> f64 + Sync) {
+    if parallel {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            rhs.par_iter_mut().enumerate().with_min_len(PARALLEL_MIN_NODES).for_each(|(j, r)| *r = node(j + 1));
+            return;
+        }
+    }
+    for (j, r) in rhs.iter_mut().enumerate() {
+        *r = node(j + 1);
+    }
+}
+
+// Largest violation of min(A x - rhs, x - floor) = 0 over the rows. The band entries outside the
+// matrix (lower[0], upper[size-1]) hold the boundary coupling and are skipped.
+fn lcp_residual(a: &TridiagonalMatrix, rhs: &[f64], x: &[f64], floor: &[f64]) -> f64 {
+    let size = x.len();
+    (0..size)
+        .map(|j| {
+            let mut ax = a.diag[j] * x[j];
+            if j > 0 { ax += a.lower[j] * x[j - 1]; }
+            if j + 1 < size { ax += a.upper[j] * x[j + 1]; }
+            (ax - rhs[j]).min(x[j] - floor[j]).abs()
+        })
+        .fold(0.0, f64::max)
+}
+
+// Exercise is optimal where the value sits on the payoff. Calls exercise above the boundary,
+// puts below it, so scan inward from the deep in-the-money end of the grid.
+fn exercise_spot(option_type: OptionType, s_grid: &[f64], intrinsic: &[f64], value: impl Fn(usize) -> f64) -> Option<f64> {
+    let exercised = |i: usize| intrinsic[i] > 0.0 && value(i) - intrinsic[i] <= 1e-8;
+    let m = s_grid.len() - 1;
+    match option_type {
+        OptionType::Call | OptionType::DigitalCall => {
+            (0..=m).rev().take_while(|&i| exercised(i)).last().map(|i| s_grid[i])
+        }
+        OptionType::Put | OptionType::DigitalPut => {
+            (0..=m).take_while(|&i| exercised(i)).last().map(|i| s_grid[i])
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garman_kohlhagen::garman_kohlhagen;
+    use crate::payoff::BarrierType;
+
+    #[test]
+    fn fast_memory_matches_exact_l1() {
+        // Small alpha needs thousands of exponentials, so it runs on a smaller grid
+        let cases = [(OptionType::Call, 0.85, 200, 400), (OptionType::Put, 0.5, 200, 400), (OptionType::Call, 0.2, 200, 400), (OptionType::Call, 0.02, 100, 200)];
+        for (option_type, alpha, m, n) in cases {
+            let params = FxOptionParams { option_type, alpha, ..FxOptionParams::eurusd_call() };
+            let exact = solve_fx_tfbs_with(&params, &SolverConfig::new(m, n)).unwrap();
+            let fast_config = SolverConfig { memory: MemoryMode::SumOfExponentials { tol: 1e-10 }, ..SolverConfig::new(m, n) };
+            let fast = solve_fx_tfbs_with(&params, &fast_config).unwrap();
+            let max_diff = exact.prices.iter().zip(&fast.prices).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+            assert!(max_diff < 1e-8, "{option_type:?} alpha {alpha}: max price difference {max_diff}");
+        }
+    }
+
+    #[test]
+    fn american_exercise_brackets_the_european_value() {
+        // Without a foreign rate early exercise of a call is never optimal, on any grid size
+        for m in [400, 8000] {
+            let call = FxOptionParams { rf: 0.0, ..FxOptionParams::eurusd_call() };
+            let european = solve_fx_tfbs(&call, m, 100).unwrap();
+            let american = solve_fx_tfbs(&FxOptionParams { exercise: ExerciseStyle::American, ..call }, m, 100).unwrap();
+            let gap = european.prices.iter().zip(&american.prices).map(|(e, a)| (a - e).abs()).fold(0.0, f64::max);
+            assert!(gap < 1e-12, "M = {m}: American call differs from European by {gap}");
+        }
+
+        for alpha in [0.85, 1.0] {
+            let put = FxOptionParams { option_type: OptionType::Put, alpha, ..FxOptionParams::eurusd_call() };
+            let european = solve_fx_tfbs(&put, 400, 200).unwrap();
+            let american = solve_fx_tfbs(&FxOptionParams { exercise: ExerciseStyle::American, ..put.clone() }, 400, 200).unwrap();
+            for (i, &s) in american.s_grid.iter().enumerate() {
+                assert!(american.prices[i] >= european.prices[i] - 1e-12, "alpha {alpha}: below European at S = {s}");
+                assert!(american.prices[i] >= put.option_type.payoff(s, put.k) - 1e-12, "alpha {alpha}: below intrinsic at S = {s}");
             }
-            history += b[step - 1] * v[(i, 0)];
-            rhs[(i - 1, 0)] = history;
+            assert!(american.price_at(1.10) > european.price_at(1.10) + 1e-4);
+            // The put's exercise boundary falls away from the strike as time to expiry grows
+            let boundary: Vec<f64> = american.exercise_boundary.iter().map(|b| b.expect("no exercise region")).collect();
+            assert!(boundary[0] < put.k);
+            assert!(boundary.windows(2).all(|w| w[1] <= w[0]), "alpha {alpha}: boundary not monotone {boundary:?}");
         }
+    }
 
-        // Apply boundary condition to the last equation in the tridiagonal system
-        rhs[(m - 2, 0)] -= upper_val * v_upper;
+    #[test]
+    fn knock_in_and_knock_out_add_up_to_the_vanilla() {
+        // Parity holds on the vanilla grid; at spot only the KO interpolation is left between them
+        let config = SolverConfig::new(400, 200);
+        for (barrier_type, level, option_type) in
+            [(BarrierType::UpAndIn, 1.30, OptionType::Call), (BarrierType::DownAndIn, 0.95, OptionType::Put)]
+        {
+            let vanilla = FxOptionParams { option_type, ..FxOptionParams::eurusd_call() };
+            let knock_in = Barrier { barrier_type, level, rebate: 0.0 };
+            let knock_out = Barrier { barrier_type: barrier_type.knock_out(), ..knock_in };
+            let price = |barrier: Option<Barrier>| solve_fx_tfbs_with(&FxOptionParams { barrier, ..vanilla.clone() }, &config).unwrap().price_at(1.10);
+            let (ki, ko, plain) = (price(Some(knock_in)), price(Some(knock_out)), price(None));
+            assert!(ki > 0.0 && ko > 0.0);
+            assert!((ki + ko - plain).abs() < 1e-6, "{barrier_type:?}: KI {ki} + KO {ko} vs vanilla {plain}");
+        }
+    }
 
-        let sol = lu.solve(&rhs);
-        for i in 1..m { v[(i, step)] = sol[(i - 1, 0)]; }
-        
-        v[(0, step)] = 0.0; // Left boundary S -> 0
-        v[(m, step)] = v_upper; // Right boundary S -> S_max
+    // Total variation of the gamma error against Garman-Kohlhagen (alpha = 1) within 10% of the strike,
+    // which is what a payoff-induced ripple adds to a smooth gamma profile
+    fn gamma_ripple(params: &FxOptionParams, smoothing: PayoffSmoothing) -> f64 {
+        let sol = solve_fx_tfbs_with(params, &SolverConfig { smoothing, ..SolverConfig::new(400, 200) }).unwrap();
+        let (_, gamma) = grid_delta_gamma(&sol.s_grid, &sol.prices);
+        let errors: Vec<f64> = (0..sol.s_grid.len())
+            .filter(|&i| (sol.s_grid[i] / params.k).ln().abs() < 0.1)
+            .map(|i| gamma[i] - garman_kohlhagen(params, sol.s_grid[i]).gamma)
+            .collect();
+        errors.windows(2).map(|w| (w[1] - w[0]).abs()).sum()
     }
 
-    (s_grid, (0..=m).map(|i| v[(i, n)]).collect())
-}
\ No newline at end of file
+    #[test]
+    fn cell_averaging_smooths_gamma_near_strike() {
+        // Two weeks to expiry, where the payoff kink dominates gamma
+        for option_type in [OptionType::Call, OptionType::DigitalCall] {
+            let params = FxOptionParams { t: 0.05, alpha: 1.0, option_type, ..FxOptionParams::eurusd_call() };
+            let raw = gamma_ripple(&params, PayoffSmoothing::None);
+            let smoothed = gamma_ripple(&params, PayoffSmoothing::CellAverage);
+            assert!(smoothed < 0.6 * raw, "{option_type:?}: gamma ripple {smoothed} smoothed vs {raw} raw");
+        }
+    }
+
+    #[test]
+    fn bad_inputs_return_errors_instead_of_panicking() {
+        let invalid = |params: FxOptionParams, m: usize, n: usize, name: &str| {
+            match solve_fx_tfbs_final_stable(params, m, n) {
+                Err(SolverError::InvalidParameter { name: got, .. }) => assert_eq!(got, name),
+                other => panic!("expected invalid {name}, got {:?}", other.map(|_| ())),
+            }
+        };
+        invalid(FxOptionParams::eurusd_call(), 2, 100, "m");
+        invalid(FxOptionParams::eurusd_call(), 100, 0, "n");
+        invalid(FxOptionParams { alpha: 0.0, ..FxOptionParams::eurusd_call() }, 100, 100, "alpha");
+        invalid(FxOptionParams { alpha: 1.2, ..FxOptionParams::eurusd_call() }, 100, 100, "alpha");
+        invalid(FxOptionParams { sigma: -0.15, ..FxOptionParams::eurusd_call() }, 100, 100, "sigma");
+        invalid(FxOptionParams { s_max: 0.11, ..FxOptionParams::eurusd_call() }, 100, 100, "s_max");
+        let nan_tol = SolverConfig { memory: MemoryMode::SumOfExponentials { tol: f64::NAN }, ..SolverConfig::new(100, 100) };
+        let result = solve_fx_tfbs_with(&FxOptionParams::eurusd_call(), &nan_tol);
+        assert!(matches!(result, Err(SolverError::InvalidParameter { name: "tol", .. })));
+
+        let soe_graded = SolverConfig {
+            memory: MemoryMode::SumOfExponentials { tol: 1e-8 }, mesh: TimeMesh::Graded { r: 2.0 }, ..SolverConfig::new(100, 100)
+        };
+        assert!(matches!(solve_fx_tfbs_with(&FxOptionParams::eurusd_call(), &soe_graded), Err(SolverError::UnsupportedCombination { .. })));
+        let rebated_in = Barrier { barrier_type: BarrierType::UpAndIn, level: 1.30, rebate: 0.05 };
+        let result = solve_fx_tfbs(&FxOptionParams { barrier: Some(rebated_in), ..FxOptionParams::eurusd_call() }, 100, 100);
+        assert!(matches!(result, Err(SolverError::UnsupportedCombination { .. })));
+        let schedule = |pieces: Vec<(f64, f64)>| FxOptionParams {
+            alpha_schedule: Some(AlphaSchedule::PiecewiseConstant(pieces)), ..FxOptionParams::eurusd_call()
+        };
+        invalid(schedule(vec![]), 100, 100, "schedule");
+        invalid(schedule(vec![(0.5, 0.9), (0.0, 0.7)]), 100, 100, "schedule");
+        invalid(schedule(vec![(0.0, 0.7), (0.0, 0.9)]), 100, 100, "schedule");
+        let unsorted = SolverConfig { mesh: TimeMesh::Custom(vec![0.0, 0.5, 0.25, 1.0]), ..SolverConfig::new(100, 3) };
+        let result = solve_fx_tfbs_with(&FxOptionParams::eurusd_call(), &unsorted);
+        assert!(matches!(result, Err(SolverError::InvalidParameter { name: "mesh", .. })));
+        // A rate this negative turns the diagonal of the implicit step negative and the values blow up
+        let result = solve_fx_tfbs(&FxOptionParams { rd: -1e300, ..FxOptionParams::eurusd_call() }, 100, 100);
+        assert!(matches!(result, Err(SolverError::SingularMatrix { .. } | SolverError::NonFiniteValue { .. })));
+    }
+
+    #[test]
+    fn slices_match_the_full_surface_at_their_dates() {
+        let soe = SolverConfig { memory: MemoryMode::SumOfExponentials { tol: 1e-10 }, ..SolverConfig::new(200, 400) };
+        let sliced = SolverConfig { storage: Storage::Slices(vec![0.25, 0.5]), ..soe.clone() };
+        let up_and_in = Barrier { barrier_type: BarrierType::UpAndIn, level: 1.30, rebate: 0.0 };
+        for params in [
+            FxOptionParams::eurusd_call(),
+            FxOptionParams { option_type: OptionType::Put, exercise: ExerciseStyle::American, ..FxOptionParams::eurusd_call() },
+            FxOptionParams { barrier: Some(up_and_in), ..FxOptionParams::eurusd_call() },
+        ] {
+            let full = solve_fx_tfbs_with(&params, &soe).unwrap();
+            let bounded = solve_fx_tfbs_with(&params, &sliced).unwrap();
+            assert_eq!(bounded.surface.ncols(), 6);
+            assert_eq!(full.prices, bounded.prices);
+            assert_eq!(full.exercise_boundary, bounded.exercise_boundary);
+            for tau in [0.0, 0.25, 0.5, 1.0] {
+                assert_eq!(full.value_at(1.15, tau), bounded.value_at(1.15, tau), "{:?} at tau {tau}", params.option_type);
+            }
+        }
+        let exact = SolverConfig { storage: Storage::Slices(vec![0.5]), ..SolverConfig::new(200, 400) };
+        assert!(matches!(solve_fx_tfbs_with(&FxOptionParams::eurusd_call(), &exact), Err(SolverError::UnsupportedCombination { .. })));
+    }
+
+    #[test]
+    fn alpha_schedules_reduce_to_constant_orders() {
+        let config = SolverConfig::new(300, 200);
+        let with_schedule = |pieces: Vec<(f64, f64)>| {
+            // The scalar alpha is deliberately wrong; the schedule must replace it everywhere
+            let params = FxOptionParams { alpha: 0.3, alpha_schedule: Some(AlphaSchedule::PiecewiseConstant(pieces)), ..FxOptionParams::eurusd_call() };
+            solve_fx_tfbs_with(&params, &config).unwrap()
+        };
+        let constant = |alpha: f64| solve_fx_tfbs_with(&FxOptionParams { alpha, ..FxOptionParams::eurusd_call() }, &config).unwrap();
+        let ulps = |a: &[f64], b: &[f64]| a.iter().zip(b).all(|(x, y)| (x - y).abs() <= f64::EPSILON * x.abs().max(y.abs()));
+
+        // A constant schedule, in one piece or several, is the scalar order
+        let scalar = constant(0.85);
+        for pieces in [vec![(0.0, 0.85)], vec![(0.0, 0.85), (0.3, 0.85), (0.6, 0.85)]] {
+            assert!(ulps(&with_schedule(pieces.clone()).prices, &scalar.prices), "{pieces:?}");
+        }
+        // Switching from 0.7 to 0.9 at 6M: the solve runs backwards from expiry, so every level within
+        // 6M of expiry has only seen alpha = 0.9 and the earlier ones differ from both constants
+        let switched = with_schedule(vec![(0.0, 0.7), (0.5, 0.9)]);
+        let (late, early) = (constant(0.9), constant(0.7));
+        for step in 0..switched.times.len() {
+            let column = switched.surface.col_as_slice(step);
+            if switched.times[step] <= 0.5 {
+                assert!(ulps(column, late.surface.col_as_slice(step)), "level {step} differs from alpha = 0.9");
+            }
+        }
+        assert!((switched.price_at(1.10) - late.price_at(1.10)).abs() > 1e-4);
+        assert!((switched.price_at(1.10) - early.price_at(1.10)).abs() > 1e-4);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_history_is_bit_identical_to_serial() {
+        let schedule = AlphaSchedule::PiecewiseConstant(vec![(0.0, 0.7), (0.5, 0.9)]);
+        for (params, config) in [
+            (FxOptionParams::eurusd_call(), SolverConfig::new(300, 200)),
+            (FxOptionParams::eurusd_call(), SolverConfig { scheme: TimeScheme::L1_2, ..SolverConfig::new(300, 200) }),
+            (FxOptionParams::eurusd_call(), SolverConfig { mesh: TimeMesh::Graded { r: 1.35 }, ..SolverConfig::new(300, 200) }),
+            (FxOptionParams { alpha_schedule: Some(schedule), ..FxOptionParams::eurusd_call() }, SolverConfig::new(300, 200)),
+        ] {
+            let serial = solve_fx_tfbs_with(&params, &config).unwrap();
+            let parallel = solve_fx_tfbs_with(&params, &SolverConfig { parallel: true, ..config.clone() }).unwrap();
+            for step in 0..serial.times.len() {
+                let (a, b) = (serial.surface.col_as_slice(step), parallel.surface.col_as_slice(step));
+                assert!(a.iter().zip(b).all(|(x, y)| x.to_bits() == y.to_bits()), "{config:?}: step {step} differs");
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Closed-form Garman-Kohlhagen prices and Greeks, the alpha = 1 limit of the time-fractional
+// equation. Covers the European vanilla and cash-or-nothing digital payoffs in OptionType;
+// exercise style and barriers in FxOptionParams are not modelled here. Greeks use the same
+// units as greeks.rs: per unit of vol and rate, theta per year of calendar time. With rate curves
+// the zero rates to expiry are used, which prices exactly; theta then treats them as flat.
+
+use statrs::distribution::{Continuous, ContinuousCDF, Normal};
+
+use crate::fractional_pde::FxOptionParams;
+use crate::payoff::OptionType;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GkGreeks {
+    pub price: f64, pub delta: f64, pub gamma: f64, pub theta: f64,
+    pub vega: f64, pub rho_d: f64, pub rho_f: f64,
+}
+
+pub fn garman_kohlhagen(params: &FxOptionParams, spot: f64) -> GkGreeks {
+    let norm = Normal::new(0.0, 1.0).unwrap();
+    let (rd, rf) = params.effective_rates();
+    let (s, k, t, sigma) = (spot, params.k, params.t, params.sigma);
+    let sqrt_t = t.sqrt();
+    let vol_t = sigma * sqrt_t;
+    let d1 = ((s / k).ln() + (rd - rf + 0.5 * sigma * sigma) * t) / vol_t;
+    let d2 = d1 - vol_t;
+    let (df_d, df_f) = ((-rd * t).exp(), (-rf * t).exp());
+    let (n_d1, n_d2) = (norm.pdf(d1), norm.pdf(d2));
+
+    match params.option_type {
+        OptionType::Call | OptionType::Put => {
+            let phi = if params.option_type == OptionType::Call { 1.0 } else { -1.0 };
+            let (cdf_d1, cdf_d2) = (norm.cdf(phi * d1), norm.cdf(phi * d2));
+            GkGreeks {
+                price: phi * (s * df_f * cdf_d1 - k * df_d * cdf_d2),
+                delta: phi * df_f * cdf_d1,
+                gamma: df_f * n_d1 / (s * vol_t),
+                theta: -s * df_f * n_d1 * sigma / (2.0 * sqrt_t) + phi * (rf * s * df_f * cdf_d1 - rd * k * df_d * cdf_d2),
+                vega: s * df_f * n_d1 * sqrt_t,
+                rho_d: phi * k * t * df_d * cdf_d2,
+                rho_f: -phi * s * t * df_f * cdf_d1,
+            }
+        }
+        OptionType::DigitalCall | OptionType::DigitalPut => {
+            // V = df_d * N(phi * d2), so every Greek is a discounting term plus phi * df_d * n(d2) * dd2/dx
+            let phi = if params.option_type == OptionType::DigitalCall { 1.0 } else { -1.0 };
+            let price = df_d * norm.cdf(phi * d2);
+            let slope = phi * df_d * n_d2;
+            let dd2_dt = (rd - rf - 0.5 * sigma * sigma) / vol_t - d2 / (2.0 * t);
+            GkGreeks {
+                price,
+                delta: slope / (s * vol_t),
+                gamma: -slope * d1 / (s * s * vol_t * vol_t),
+                theta: rd * price - slope * dd2_dt,
+                vega: -slope * d1 / sigma,
+                rho_d: -t * price + slope * sqrt_t / sigma,
+                rho_f: -slope * sqrt_t / sigma,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractional_pde::solve_fx_tfbs;
+
+    fn eurusd(option_type: OptionType, alpha: f64) -> FxOptionParams {
+        FxOptionParams { option_type, alpha, ..FxOptionParams::eurusd_call() }
+    }
+
+    #[test]
+    fn alpha_one_converges_to_gk_under_grid_refinement() {
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let params = eurusd(option_type, 1.0);
+            let exact = garman_kohlhagen(&params, 1.10).price;
+            let errors: Vec<f64> = [(50, 25), (100, 50), (200, 100), (400, 200)].iter()
+                .map(|&(m, n)| (solve_fx_tfbs(&params, m, n).unwrap().price_at(1.10) - exact).abs())
+                .collect();
+            // Space and time errors have opposite signs, so the sequence need not be monotone;
+            // require an overall first-order-or-better reduction and a small final error
+            assert!(errors[3] < errors[0] / 8.0, "{option_type:?}: errors {errors:?}");
+            assert!(errors[3] < 1.5e-4, "{option_type:?}: finest-grid error {}", errors[3]);
+        }
+    }
+
+    #[test]
+    fn fractional_price_tends_to_gk_as_alpha_tends_to_one() {
+        let exact = garman_kohlhagen(&eurusd(OptionType::Call, 1.0), 1.10).price;
+        let grid_limit = solve_fx_tfbs(&eurusd(OptionType::Call, 1.0), 400, 200).unwrap().price_at(1.10);
+        let gaps: Vec<f64> = [0.8, 0.9, 0.99, 0.999].iter()
+            .map(|&alpha| (solve_fx_tfbs(&eurusd(OptionType::Call, alpha), 400, 200).unwrap().price_at(1.10) - grid_limit).abs())
+            .collect();
+        assert!(gaps.windows(2).all(|w| w[1] < w[0]), "gaps to the alpha = 1 solve not decreasing {gaps:?}");
+        assert!(gaps[3] < 1e-4, "alpha = 0.999 gap {}", gaps[3]);
+        assert!((grid_limit - exact).abs() < 1e-3, "alpha = 1 solve {grid_limit} vs GK {exact}");
+    }
+}
@@ -0,0 +1,157 @@
+// Greeks on the solver's spatial grid. Delta and gamma use three-point finite differences on
+// the non-uniform S nodes of a single solve; theta, vega, rho_d, rho_f and the sensitivity to
+// alpha come from bump-and-reprice on the same grid so the discretisation error largely cancels.
+// Rho bumps are parallel shifts of the flat rate or of the whole curve.
+// Sensitivities are per unit change (vega per 1.00 of vol, rho per 1.00 of rate) and theta is
+// per year of calendar time.
+
+use crate::caputo::TimeMesh;
+use crate::fractional_pde::{FxOptionParams, SolverConfig, SolverError, solve_fx_tfbs_with};
+use crate::memory::Storage;
+
+const VOL_BUMP: f64 = 1e-3;
+const RATE_BUMP: f64 = 1e-4;
+const ALPHA_BUMP: f64 = 1e-3;
+const THETA_BUMP: f64 = 1.0 / 365.0;
+
+pub struct FxGreeks {
+    pub s_grid: Vec<f64>,
+    pub price: Vec<f64>,
+    pub delta: Vec<f64>,
+    pub gamma: Vec<f64>,
+    pub theta: Vec<f64>,
+    pub vega: Vec<f64>,
+    pub rho_d: Vec<f64>,
+    pub rho_f: Vec<f64>,
+    pub alpha_sens: Vec<f64>,
+}
+
+pub fn compute_greeks(params: &FxOptionParams, config: &SolverConfig) -> Result<FxGreeks, SolverError> {
+    let base = solve_fx_tfbs_with(params, config)?;
+    let (delta, gamma) = grid_delta_gamma(&base.s_grid, &base.prices);
+
+    let reprice = |p: FxOptionParams| solve_fx_tfbs_with(&p, config).map(|sol| sol.prices);
+    let central = |up: Vec<f64>, down: Vec<f64>, h: f64| -> Vec<f64> {
+        up.iter().zip(&down).map(|(u, d)| (u - d) / h).collect()
+    };
+
+    let vega = central(
+        reprice(FxOptionParams { sigma: params.sigma + VOL_BUMP, ..params.clone() })?,
+        reprice(FxOptionParams { sigma: params.sigma - VOL_BUMP, ..params.clone() })?,
+        2.0 * VOL_BUMP,
+    );
+    let rho_d = central(
+        reprice(params.with_rate_shift(RATE_BUMP, 0.0))?,
+        reprice(params.with_rate_shift(-RATE_BUMP, 0.0))?,
+        2.0 * RATE_BUMP,
+    );
+    let rho_f = central(
+        reprice(params.with_rate_shift(0.0, RATE_BUMP))?,
+        reprice(params.with_rate_shift(0.0, -RATE_BUMP))?,
+        2.0 * RATE_BUMP,
+    );
+    // alpha lives in (0, 1], so the up bump is cut short wherever the order is within ALPHA_BUMP of 1.
+    // The denominator takes the shift actually applied at the solve's time levels; if a schedule is
+    // capped at some levels and not others, the difference falls back to the down side alone.
+    let (alpha_up, alpha_down) = (params.with_alpha_shift(ALPHA_BUMP), params.with_alpha_shift(-ALPHA_BUMP));
+    let up_bumps: Vec<f64> = config.mesh.nodes(params.t, config.n).iter()
+        .map(|tau| alpha_up.alpha_at(params.t - tau) - params.alpha_at(params.t - tau))
+        .collect();
+    let down = reprice(alpha_down)?;
+    let alpha_sens = if up_bumps.iter().all(|h| (h - up_bumps[0]).abs() < 1e-12) {
+        central(reprice(alpha_up)?, down, up_bumps[0] + ALPHA_BUMP)
+    } else {
+        central(base.prices.clone(), down, ALPHA_BUMP)
+    };
+    // Calendar theta: one day passes, so time to expiry shrinks. A custom time mesh is scaled to
+    // the shorter expiry and kept slices are held inside it.
+    let theta_h = THETA_BUMP.min(0.5 * params.t);
+    let t_theta = params.t - theta_h;
+    let theta_config = SolverConfig {
+        mesh: match &config.mesh {
+            TimeMesh::Custom(nodes) => TimeMesh::Custom(nodes.iter().map(|tau| tau * t_theta / params.t).collect()),
+            mesh => mesh.clone(),
+        },
+        storage: match &config.storage {
+            Storage::Slices(taus) => Storage::Slices(taus.iter().map(|tau| tau.min(t_theta)).collect()),
+            Storage::Full => Storage::Full,
+        },
+        ..config.clone()
+    };
+    let theta = central(
+        solve_fx_tfbs_with(&FxOptionParams { t: t_theta, ..params.clone() }, &theta_config)?.prices,
+        base.prices.clone(),
+        theta_h,
+    );
+
+    Ok(FxGreeks { s_grid: base.s_grid, price: base.prices, delta, gamma, theta, vega, rho_d, rho_f, alpha_sens })
+}
+
+// Three-point first and second derivatives on a non-uniform grid. The end nodes take a
+// one-sided delta and copy the neighbouring gamma.
+pub fn grid_delta_gamma(s_grid: &[f64], values: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let m = s_grid.len() - 1;
+    let mut delta = vec![0.0; m + 1];
+    let mut gamma = vec![0.0; m + 1];
+    for i in 1..m {
+        let (hm, hp) = (s_grid[i] - s_grid[i - 1], s_grid[i + 1] - s_grid[i]);
+        let (vm, v0, vp) = (values[i - 1], values[i], values[i + 1]);
+        delta[i] = -hp / (hm * (hm + hp)) * vm + (hp - hm) / (hm * hp) * v0 + hm / (hp * (hm + hp)) * vp;
+        gamma[i] = 2.0 * (vm / (hm * (hm + hp)) - v0 / (hm * hp) + vp / (hp * (hm + hp)));
+    }
+    delta[0] = (values[1] - values[0]) / (s_grid[1] - s_grid[0]);
+    delta[m] = (values[m] - values[m - 1]) / (s_grid[m] - s_grid[m - 1]);
+    gamma[0] = gamma[1];
+    gamma[m] = gamma[m - 1];
+    (delta, gamma)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alpha_schedule::AlphaSchedule;
+    use crate::garman_kohlhagen::garman_kohlhagen;
+    use crate::grid::SpaceGrid;
+
+    #[test]
+    fn alpha_one_greeks_match_garman_kohlhagen() {
+        // The sinh grid puts the strike on a node, so the Greeks at the money need no interpolation.
+        // Gamma (about 2.3 here) is compared relative to its size, the rest absolutely.
+        let params = FxOptionParams { alpha: 1.0, ..FxOptionParams::eurusd_call() };
+        let config = SolverConfig { grid: SpaceGrid::Sinh { concentration: 0.1 }, ..SolverConfig::new(150, 150) };
+        let greeks = compute_greeks(&params, &config).unwrap();
+        let i = greeks.s_grid.iter().position(|&s| s == params.k).unwrap();
+        let gk = garman_kohlhagen(&params, params.k);
+        for (name, pde, exact) in [
+            ("delta", greeks.delta[i], gk.delta),
+            ("gamma", greeks.gamma[i], gk.gamma),
+            ("theta", greeks.theta[i], gk.theta),
+            ("vega", greeks.vega[i], gk.vega),
+            ("rho_d", greeks.rho_d[i], gk.rho_d),
+            ("rho_f", greeks.rho_f[i], gk.rho_f),
+        ] {
+            assert!((pde - exact).abs() < 4e-3 * exact.abs().max(1.0), "{name}: {pde} vs GK {exact}");
+        }
+    }
+
+    #[test]
+    fn alpha_sensitivity_uses_the_bump_the_schedule_received() {
+        let config = SolverConfig::new(200, 100);
+        let scalar = compute_greeks(&FxOptionParams::eurusd_call(), &config).unwrap();
+        // A constant schedule next to a scalar alpha at the cap: the schedule gets the full central bump
+        let schedule = AlphaSchedule::PiecewiseConstant(vec![(0.0, 0.85)]);
+        let params = FxOptionParams { alpha: 1.0, alpha_schedule: Some(schedule), ..FxOptionParams::eurusd_call() };
+        let scheduled = compute_greeks(&params, &config).unwrap();
+        for (a, b) in scalar.alpha_sens.iter().zip(&scheduled.alpha_sens) {
+            assert!((a - b).abs() <= 1e-12 * a.abs().max(1.0), "{a} vs {b}");
+        }
+        // Partly at the cap: one-sided, so close to (but not exactly) the central difference
+        let capped = AlphaSchedule::PiecewiseConstant(vec![(0.0, 0.85), (0.5, 1.0)]);
+        let params = FxOptionParams { alpha_schedule: Some(capped), ..FxOptionParams::eurusd_call() };
+        let greeks = compute_greeks(&params, &config).unwrap();
+        let down = solve_fx_tfbs_with(&params.with_alpha_shift(-ALPHA_BUMP), &config).unwrap().prices;
+        for ((sens, price), down) in greeks.alpha_sens.iter().zip(&greeks.price).zip(&down) {
+            assert_eq!(*sens, (price - down) / ALPHA_BUMP);
+        }
+    }
+}
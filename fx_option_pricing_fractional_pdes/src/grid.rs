@@ -0,0 +1,123 @@
+use crate::fractional_pde::FxOptionParams;
+
+// Extent of the spatial grid. Fixed is the original [K/10, s_max]. StdDevs spans `width`
+// standard deviations sigma * sqrt(T) of log-spot beyond both the forward of `spot` and the
+// strike, so the domain follows the vol and expiry (puts and high-vol crosses need far more
+// room below the strike than K/10) and s_max is ignored. Range takes the spot bounds as given.
+// A knock-out barrier still replaces the end on its side.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Domain { Fixed, StdDevs { spot: f64, width: f64 }, Range { s_min: f64, s_max: f64 } }
+
+impl Domain {
+    // (x_min, x_max) in log-spot before any barrier truncation
+    pub fn bounds(&self, params: &FxOptionParams) -> (f64, f64) {
+        match *self {
+            Domain::Fixed => ((params.k / 10.0).ln(), params.s_max.ln()),
+            Domain::StdDevs { spot, width } => {
+                let (df_d, df_f) = params.discount_factors(0.0, params.t);
+                let x_fwd = (spot * df_f / df_d).ln();
+                let spread = width * params.sigma * params.t.sqrt();
+                (x_fwd.min(params.k.ln()) - spread, x_fwd.max(params.k.ln()) + spread)
+            }
+            Domain::Range { s_min, s_max } => (s_min.ln(), s_max.ln()),
+        }
+    }
+}
+
+// Placement of the M + 1 spatial nodes between x_min and x_max in log-spot.
+//
+// Uniform keeps the equal log-spacing dx = (x_max - x_min) / M. Sinh clusters nodes around
+// ln K with x = ln K + c * sinh(xi) for xi uniform on each side of the strike, so the spacing
+// near the money is about c * dxi and grows exponentially into the wings; a smaller
+// `concentration` c packs the nodes tighter. The strike is always a node of the sinh grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpaceGrid { Uniform, Sinh { concentration: f64 } }
+
+impl SpaceGrid {
+    // Log-spot nodes and the matching spot nodes. The end nodes are x_min and x_max exactly (a
+    // knock-out barrier stays on the boundary) and a sinh grid's centre node is k itself.
+    pub fn nodes(&self, x_min: f64, x_max: f64, k: f64, m: usize) -> (Vec<f64>, Vec<f64>) {
+        let (x, centre) = match *self {
+            SpaceGrid::Uniform => {
+                let dx = (x_max - x_min) / m as f64;
+                ((0..=m).map(|i| x_min + i as f64 * dx).collect(), None)
+            }
+            SpaceGrid::Sinh { concentration: c } => {
+                let x_c = k.ln().clamp(x_min, x_max);
+                let xi_lo = ((x_min - x_c) / c).asinh();
+                let xi_hi = ((x_max - x_c) / c).asinh();
+                // Node index of the strike: its share of the xi range, kept off the boundary when
+                // the strike lies strictly inside the domain
+                let mut i_c = (m as f64 * -xi_lo / (xi_hi - xi_lo)).round() as usize;
+                if x_c > x_min && x_c < x_max { i_c = i_c.clamp(1, m - 1); }
+                let x: Vec<f64> = (0..=m)
+                    .map(|i| match i {
+                        0 => x_min,
+                        _ if i == m => x_max,
+                        _ if i < i_c => x_c + c * (xi_lo * (1.0 - i as f64 / i_c as f64)).sinh(),
+                        _ => x_c + c * (xi_hi * (i - i_c) as f64 / (m - i_c) as f64).sinh(),
+                    })
+                    .collect();
+                (x, (x_c == k.ln()).then_some(i_c))
+            }
+        };
+        let mut s: Vec<f64> = x.iter().map(|x| x.exp()).collect();
+        if let Some(i_c) = centre { s[i_c] = k; }
+        (x, s)
+    }
+
+    pub fn is_uniform(&self) -> bool {
+        matches!(self, SpaceGrid::Uniform)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::caputo::TimeScheme;
+    use crate::fractional_pde::{FxOptionParams, SolverConfig, solve_fx_tfbs_with};
+    use crate::garman_kohlhagen::garman_kohlhagen;
+    use crate::payoff::{BoundaryCondition, OptionType};
+
+    #[test]
+    fn sinh_grid_keeps_strike_and_ends_on_nodes() {
+        let (x_min, x_max) = (0.11f64.ln(), 20.0f64.ln());
+        for (k, m) in [(1.10, 400), (1.37, 101), (0.95, 40)] {
+            let (x, s) = SpaceGrid::Sinh { concentration: 0.1 }.nodes(x_min, x_max, k, m);
+            assert!(s.contains(&k), "strike {k} is not a node for M = {m}");
+            assert_eq!((x[0], x[m]), (x_min, x_max));
+            assert!(x.windows(2).all(|w| w[1] > w[0]));
+        }
+    }
+
+    #[test]
+    fn sinh_grid_beats_uniform_at_equal_node_count() {
+        let params = FxOptionParams { alpha: 1.0, ..FxOptionParams::eurusd_call() };
+        let gk = garman_kohlhagen(&params, 1.10).price;
+        let error = |grid: SpaceGrid| {
+            let config = SolverConfig { grid, scheme: TimeScheme::L1_2, ..SolverConfig::new(100, 400) };
+            (solve_fx_tfbs_with(&params, &config).unwrap().price_at(1.10) - gk).abs()
+        };
+        let (uniform, sinh) = (error(SpaceGrid::Uniform), error(SpaceGrid::Sinh { concentration: 0.1 }));
+        assert!(sinh < uniform / 4.0, "sinh error {sinh} vs uniform {uniform}");
+    }
+
+    #[test]
+    fn std_dev_domain_prices_high_vol_put_with_any_boundary_condition() {
+        // USDTRY-like: wide rate differential and vol, so K/10 to s_max misplaces the domain
+        let params = FxOptionParams {
+            s_max: 45.0, k: 34.0, rd: 0.40, rf: 0.05, sigma: 0.35, alpha: 1.0, option_type: OptionType::Put,
+            ..FxOptionParams::eurusd_call()
+        };
+        let gk = garman_kohlhagen(&params, 32.0).price;
+        let fixed = solve_fx_tfbs_with(&params, &SolverConfig::new(200, 200)).unwrap().price_at(32.0);
+        assert!((fixed - gk).abs() > 1e-2, "fixed domain unexpectedly accurate: {fixed} vs {gk}");
+        for bc in [BoundaryCondition::Dirichlet, BoundaryCondition::Neumann, BoundaryCondition::Linearity] {
+            let config = SolverConfig {
+                domain: Domain::StdDevs { spot: 32.0, width: 4.0 }, lower_bc: bc, upper_bc: bc, ..SolverConfig::new(200, 200)
+            };
+            let price = solve_fx_tfbs_with(&params, &config).unwrap().price_at(32.0);
+            assert!((price - gk).abs() < 3e-3, "{bc:?}: {price} vs GK {gk}");
+        }
+    }
+}
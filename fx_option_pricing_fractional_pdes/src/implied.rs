@@ -0,0 +1,199 @@
+// Inverts the fractional solver for sigma (alpha fixed) or alpha (sigma fixed) from a quoted
+// premium at a given spot. The search interval is scanned first and Brent's method refines every
+// bracketing sub-interval, so payoffs whose price is not monotone in the parameter are handled.
+// Vanilla prices are hump-shaped in alpha, so a premium below the peak is usually explained by
+// two values of alpha; implied_alpha returns all of them. Digitals can do the same in sigma, in
+// which case implied_sigma reports MultipleSolutions rather than picking one.
+
+use std::fmt;
+
+use crate::fractional_pde::{FxOptionParams, SolverConfig, SolverError, solve_fx_tfbs_with};
+
+const SIGMA_RANGE: (f64, f64) = (1e-3, 3.0);
+const ALPHA_RANGE: (f64, f64) = (0.05, 1.0);
+const SCAN_POINTS: usize = 16;
+const X_TOL: f64 = 1e-8;
+const MAX_ITER: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImpliedError {
+    // The target (premium or delta) is not attained anywhere on the search interval; the scanned range is reported
+    NoSolution { target: f64, min_value: f64, max_value: f64 },
+    // More than one parameter value reproduces the target
+    MultipleSolutions { roots: Vec<f64> },
+    // Brent's method did not reach the tolerance within MAX_ITER iterations
+    NotConverged { last: f64 },
+    // The solver produced a non-finite price during the search
+    NonFinitePrice { at: f64 },
+    // The solver rejected the inputs or failed at the given parameter value
+    Solver { at: f64, error: SolverError },
+    // The search cannot vary the parameter with this feature set (an alpha(t) schedule overrides alpha)
+    Unsupported { feature: &'static str },
+}
+
+impl fmt::Display for ImpliedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImpliedError::NoSolution { target, min_value, max_value } => {
+                write!(f, "target {target} outside attainable range [{min_value}, {max_value}]")
+            }
+            ImpliedError::MultipleSolutions { roots } => write!(f, "target is matched by several values {roots:?}"),
+            ImpliedError::NotConverged { last } => write!(f, "root search did not converge (last iterate {last})"),
+            ImpliedError::NonFinitePrice { at } => write!(f, "solver returned a non-finite price at {at}"),
+            ImpliedError::Solver { at, error } => write!(f, "solver failed at {at}: {error}"),
+            ImpliedError::Unsupported { feature } => write!(f, "implied alpha does not support {feature}"),
+        }
+    }
+}
+
+impl std::error::Error for ImpliedError {}
+
+pub fn implied_sigma(params: &FxOptionParams, config: &SolverConfig, spot: f64, premium: f64) -> Result<f64, ImpliedError> {
+    let price = |sigma: f64| solve_fx_tfbs_with(&FxOptionParams { sigma, ..params.clone() }, config).map(|sol| sol.price_at(spot));
+    let roots = find_roots(price, premium, SIGMA_RANGE)?;
+    if roots.len() > 1 { return Err(ImpliedError::MultipleSolutions { roots }); }
+    Ok(roots[0])
+}
+
+// Every alpha in ALPHA_RANGE that reproduces the premium, in ascending order. A schedule in `params`
+// would replace the scalar alpha being searched, so it is rejected; implied_sigma keeps it.
+pub fn implied_alpha(params: &FxOptionParams, config: &SolverConfig, spot: f64, premium: f64) -> Result<Vec<f64>, ImpliedError> {
+    if params.alpha_schedule.is_some() { return Err(ImpliedError::Unsupported { feature: "alpha(t) schedules" }); }
+    let price = |alpha: f64| solve_fx_tfbs_with(&FxOptionParams { alpha, ..params.clone() }, config).map(|sol| sol.price_at(spot));
+    find_roots(price, premium, ALPHA_RANGE)
+}
+
+// All x in [lo, hi] with value(x) = target, in ascending order
+pub(crate) fn find_roots(
+    value: impl Fn(f64) -> Result<f64, SolverError>, target: f64, (lo, hi): (f64, f64),
+) -> Result<Vec<f64>, ImpliedError> {
+    let objective = |x: f64| -> Result<f64, ImpliedError> {
+        let p = value(x).map_err(|error| ImpliedError::Solver { at: x, error })?;
+        if p.is_finite() { Ok(p - target) } else { Err(ImpliedError::NonFinitePrice { at: x }) }
+    };
+
+    let xs: Vec<f64> = (0..=SCAN_POINTS).map(|i| lo + (hi - lo) * i as f64 / SCAN_POINTS as f64).collect();
+    let mut fs = Vec::with_capacity(xs.len());
+    let mut roots = Vec::new();
+    for &x in &xs {
+        let f = objective(x)?;
+        if f == 0.0 { roots.push(x); }
+        if let Some(&f_prev) = fs.last() && f_prev * f < 0.0 {
+            roots.push(brent(&objective, xs[fs.len() - 1], x, f_prev, f)?);
+        }
+        fs.push(f);
+    }
+    if !roots.is_empty() { return Ok(roots); }
+    let min_value = fs.iter().cloned().fold(f64::INFINITY, f64::min) + target;
+    let max_value = fs.iter().cloned().fold(f64::NEG_INFINITY, f64::max) + target;
+    Err(ImpliedError::NoSolution { target, min_value, max_value })
+}
+
+// Brent's method on a bracket [a, b] with f(a) * f(b) < 0
+fn brent(f: &impl Fn(f64) -> Result<f64, ImpliedError>, a: f64, b: f64, fa: f64, fb: f64) -> Result<f64, ImpliedError> {
+    let (mut a, mut b, mut fa, mut fb) = (a, b, fa, fb);
+    if fa.abs() < fb.abs() {
+        std::mem::swap(&mut a, &mut b);
+        std::mem::swap(&mut fa, &mut fb);
+    }
+    let (mut c, mut fc) = (a, fa);
+    let mut d = b - a;
+    let mut bisected = true;
+    for _ in 0..MAX_ITER {
+        if fb == 0.0 || (b - a).abs() < X_TOL { return Ok(b); }
+        let mut s = if fa != fc && fb != fc {
+            // Inverse quadratic interpolation
+            a * fb * fc / ((fa - fb) * (fa - fc)) + b * fa * fc / ((fb - fa) * (fb - fc)) + c * fa * fb / ((fc - fa) * (fc - fb))
+        } else {
+            b - fb * (b - a) / (fb - fa)
+        };
+        let between = (s - (3.0 * a + b) / 4.0) * (s - b) < 0.0;
+        let step_too_small = if bisected { (s - b).abs() >= (b - c).abs() / 2.0 || (b - c).abs() < X_TOL }
+            else { (s - b).abs() >= (c - d).abs() / 2.0 || (c - d).abs() < X_TOL };
+        bisected = !between || step_too_small;
+        if bisected { s = 0.5 * (a + b); }
+        let fs = f(s)?;
+        d = c;
+        c = b;
+        fc = fb;
+        if fa * fs < 0.0 { b = s; fb = fs; } else { a = s; fa = fs; }
+        if fa.abs() < fb.abs() {
+            std::mem::swap(&mut a, &mut b);
+            std::mem::swap(&mut fa, &mut fb);
+        }
+    }
+    Err(ImpliedError::NotConverged { last: b })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alpha_schedule::AlphaSchedule;
+    use crate::payoff::OptionType;
+
+    fn price(params: &FxOptionParams, config: &SolverConfig) -> f64 {
+        solve_fx_tfbs_with(params, config).unwrap().price_at(1.10)
+    }
+
+    #[test]
+    fn round_trips_sigma_and_both_alpha_roots() {
+        let config = SolverConfig::new(200, 100);
+        let params = FxOptionParams::eurusd_call();
+        let premium = price(&FxOptionParams { sigma: 0.12, ..params.clone() }, &config);
+        let sigma = implied_sigma(&params, &config, 1.10, premium).unwrap();
+        assert!((sigma - 0.12).abs() < 1e-6, "sigma {sigma}");
+
+        // Below the hump in alpha the premium has a second preimage, returned in ascending order
+        let roots = implied_alpha(&params, &config, 1.10, price(&params, &config)).unwrap();
+        assert_eq!(roots.len(), 2, "{roots:?}");
+        assert!(roots[0] < roots[1]);
+        assert!((roots[1] - 0.85).abs() < 1e-6, "{roots:?}");
+        let other = price(&FxOptionParams { alpha: roots[0], ..params.clone() }, &config);
+        assert!((other - price(&params, &config)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn alpha_schedules_are_kept_for_sigma_and_rejected_for_alpha() {
+        let config = SolverConfig::new(200, 100);
+        let schedule = AlphaSchedule::PiecewiseConstant(vec![(0.0, 0.85)]);
+        let params = FxOptionParams { alpha_schedule: Some(schedule), ..FxOptionParams::eurusd_call() };
+        let premium = price(&FxOptionParams { sigma: 0.12, ..params.clone() }, &config);
+        let sigma = implied_sigma(&params, &config, 1.10, premium).unwrap();
+        assert!((sigma - 0.12).abs() < 1e-6, "sigma {sigma}");
+        let result = implied_alpha(&params, &config, 1.10, price(&params, &config));
+        assert_eq!(result, Err(ImpliedError::Unsupported { feature: "alpha(t) schedules" }));
+    }
+
+    #[test]
+    fn unreachable_premium_reports_the_scanned_range() {
+        let config = SolverConfig::new(200, 100);
+        let params = FxOptionParams::eurusd_call();
+        match implied_alpha(&params, &config, 1.10, 0.10) {
+            Err(ImpliedError::NoSolution { target, min_value, max_value }) => {
+                assert_eq!(target, 0.10);
+                assert!(min_value < max_value && max_value < target);
+                // Both ends of the interval are scan points
+                for alpha in [ALPHA_RANGE.0, ALPHA_RANGE.1] {
+                    let p = price(&FxOptionParams { alpha, ..params.clone() }, &config);
+                    assert!(min_value <= p && p <= max_value, "price {p} at alpha {alpha} outside [{min_value}, {max_value}]");
+                }
+            }
+            other => panic!("expected NoSolution, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn out_of_the_money_digital_has_two_implied_vols() {
+        // An out-of-the-money digital is worth nothing at zero and at very high vol, so any premium
+        // under its peak is matched twice
+        let config = SolverConfig::new(200, 100);
+        let params = FxOptionParams { k: 1.20, option_type: OptionType::DigitalCall, ..FxOptionParams::eurusd_call() };
+        match implied_sigma(&params, &config, 1.10, price(&params, &config)) {
+            Err(ImpliedError::MultipleSolutions { roots }) => {
+                assert!(roots.windows(2).all(|w| w[0] < w[1]), "{roots:?}");
+                assert!(roots.iter().any(|r| (r - 0.15).abs() < 1e-6), "{roots:?}");
+            }
+            other => panic!("expected MultipleSolutions, got {other:?}"),
+        }
+    }
+}
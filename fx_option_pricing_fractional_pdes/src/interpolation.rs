@@ -0,0 +1,35 @@
+// Monotone piecewise-cubic (Fritsch-Carlson / PCHIP) interpolation. Option values are smooth
+// in log-spot away from the strike, and a monotone cubic keeps the kink from producing
+// overshoot, so the solver interpolates in x = ln(S). Queries outside the grid are clamped.
+
+pub fn pchip(xs: &[f64], ys: &[f64], x: f64) -> f64 {
+    hermite(xs.len() - 1, |i| xs[i], ys, x, xs.partition_point(|&xi| xi <= x))
+}
+
+// PCHIP in log-spot on an S grid; only the bracketing nodes are mapped to log space
+pub fn pchip_log(s_grid: &[f64], values: &[f64], s: f64) -> f64 {
+    hermite(s_grid.len() - 1, |i| s_grid[i].ln(), values, s.ln(), s_grid.partition_point(|&si| si <= s))
+}
+
+fn hermite(n: usize, xs: impl Fn(usize) -> f64, ys: &[f64], x: f64, upper: usize) -> f64 {
+    if upper == 0 { return ys[0]; }
+    if upper > n { return ys[n]; }
+    let j = upper;
+    let h = xs(j) - xs(j - 1);
+    let u = (x - xs(j - 1)) / h;
+    let (h00, h10) = ((1.0 + 2.0 * u) * (1.0 - u).powi(2), u * (1.0 - u).powi(2));
+    let (h01, h11) = (u.powi(2) * (3.0 - 2.0 * u), u.powi(2) * (u - 1.0));
+    h00 * ys[j - 1] + h10 * h * node_slope(n, &xs, ys, j - 1) + h01 * ys[j] + h11 * h * node_slope(n, &xs, ys, j)
+}
+
+// Weighted harmonic mean of the neighbouring secants, zero at local extrema
+fn node_slope(n: usize, xs: &impl Fn(usize) -> f64, ys: &[f64], i: usize) -> f64 {
+    let secant = |k: usize| (ys[k + 1] - ys[k]) / (xs(k + 1) - xs(k));
+    if i == 0 { return secant(0); }
+    if i == n { return secant(n - 1); }
+    let (d0, d1) = (secant(i - 1), secant(i));
+    if d0 * d1 <= 0.0 { return 0.0; }
+    let (h0, h1) = (xs(i) - xs(i - 1), xs(i + 1) - xs(i));
+    let (w0, w1) = (2.0 * h1 + h0, h1 + 2.0 * h0);
+    (w0 + w1) / (w0 / d0 + w1 / d1)
+}
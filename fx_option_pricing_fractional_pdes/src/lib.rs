@@ -0,0 +1,17 @@
+pub mod alpha_schedule;
+pub mod calibration;
+pub mod caputo;
+pub mod convergence;
+pub mod curves;
+pub mod delta_convention;
+pub mod fractional_pde;
+pub mod garman_kohlhagen;
+pub mod greeks;
+pub mod grid;
+pub mod implied;
+pub mod interpolation;
+pub mod memory;
+pub mod monte_carlo;
+pub mod payoff;
+pub mod portfolio;
+pub mod tridiagonal;
@@ -29,20 +29,164 @@
  FX Asian options where non-local memory term complicates early exercise boundary)
 */
 
-mod fractional_pde;
+use fx_option_pricing_fractional_pdes::alpha_schedule::AlphaSchedule;
+use fx_option_pricing_fractional_pdes::calibration::{calibrate_sigma_alpha, read_quotes};
+use fx_option_pricing_fractional_pdes::caputo::{TimeMesh, TimeScheme};
+use fx_option_pricing_fractional_pdes::convergence::convergence_study;
+use std::sync::Arc;
 
-use fractional_pde::{FxOptionParams, solve_fx_tfbs_final_stable};
+use fx_option_pricing_fractional_pdes::curves::{RateCurves, bootstrap_from_file};
+use fx_option_pricing_fractional_pdes::delta_convention::{DeltaConvention, fractional_strike_from_delta, gk_strike_from_delta};
+use fx_option_pricing_fractional_pdes::fractional_pde::{FxOptionParams, SolverConfig, SolverError, solve_fx_tfbs, solve_fx_tfbs_with};
+use fx_option_pricing_fractional_pdes::garman_kohlhagen::garman_kohlhagen;
+use fx_option_pricing_fractional_pdes::greeks::compute_greeks;
+use fx_option_pricing_fractional_pdes::grid::{Domain, SpaceGrid};
+use fx_option_pricing_fractional_pdes::implied::{implied_alpha, implied_sigma};
+use fx_option_pricing_fractional_pdes::memory::{MemoryMode, Storage};
+use fx_option_pricing_fractional_pdes::monte_carlo::{McConfig, price_monte_carlo};
+use fx_option_pricing_fractional_pdes::payoff::{Barrier, BarrierType, BoundaryCondition, ExerciseStyle, OptionType};
+use fx_option_pricing_fractional_pdes::portfolio::{Trade, price_portfolio};
 
-fn main() {
+fn main() -> Result<(), SolverError> {
     // s_max: Max XR in grid, M = no of spatial steps, N = no of time steps (M, N are second, third
-    // args in solve_fx_tfbs_final_stable)
-    let params = FxOptionParams { s_max: 20.0, k: 1.10, t: 1.0, rd: 0.04, rf: 0.02, sigma: 0.15, alpha: 0.85 };
-    let (s, prices) = solve_fx_tfbs_final_stable(params, 400, 200);
-    if let Some(pos) = s.iter().position(|&x| x >= 1.10) {
-        println!("Stable Price at Spot {:.4}: {:.6}", s[pos], prices[pos]);
+    // args in solve_fx_tfbs)
+    let params = FxOptionParams::eurusd_call();
+    let put_params = FxOptionParams { option_type: OptionType::Put, ..params.clone() };
+    let american_put_params = FxOptionParams { exercise: ExerciseStyle::American, ..put_params.clone() };
+    let up_and_out = Barrier { barrier_type: BarrierType::UpAndOut, level: 1.30, rebate: 0.0 };
+    let ko_params = FxOptionParams { barrier: Some(up_and_out), ..params.clone() };
+    let greeks = compute_greeks(&params, &SolverConfig::new(400, 200))?;
+    let call = solve_fx_tfbs(&params, 400, 200)?;
+    println!("Stable Price at Spot {:.4}: {:.6}", 1.10, call.price_at(1.10));
+    println!("Price at Spot {:.4} with 6M to expiry: {:.6}", 1.10, call.value_at(1.10, 0.5));
+    // 2000 x 4000 grid in bounded memory: compressed history and only the 6M slice kept
+    let bounded_config = SolverConfig {
+        memory: MemoryMode::SumOfExponentials { tol: 1e-10 }, storage: Storage::Slices(vec![0.5]), ..SolverConfig::new(2000, 4000)
+    };
+    let bounded = solve_fx_tfbs_with(&params, &bounded_config)?;
+    println!(
+        "Bounded-memory Price at Spot {:.4}: {:.6} (6M to expiry {:.6}, {} of 4001 levels stored)",
+        1.10, bounded.price_at(1.10), bounded.value_at(1.10, 0.5), bounded.times.len(),
+    );
+    let l1_2 = solve_fx_tfbs_with(&params, &SolverConfig { scheme: TimeScheme::L1_2, ..SolverConfig::new(400, 200) })?;
+    println!("Stable Price (L1-2 scheme) at Spot {:.4}: {:.6}", 1.10, l1_2.price_at(1.10));
+    // Steps graded towards expiry with r = (2 - alpha) / alpha for the full L1 order
+    let graded_config = SolverConfig { mesh: TimeMesh::Graded { r: (2.0 - 0.85) / 0.85 }, ..SolverConfig::new(400, 200) };
+    let graded = solve_fx_tfbs_with(&params, &graded_config)?;
+    println!("Stable Price (graded time mesh) at Spot {:.4}: {:.6}", 1.10, graded.price_at(1.10));
+    // A quarter of the nodes, clustered around the strike
+    let sinh_config = SolverConfig { grid: SpaceGrid::Sinh { concentration: 0.1 }, ..SolverConfig::new(100, 200) };
+    let sinh = solve_fx_tfbs_with(&params, &sinh_config)?;
+    println!("Stable Price (sinh grid, M = 100) at Spot {:.4}: {:.6}", 1.10, sinh.price_at(1.10));
+    // Grid convergence from 100 x 50 to 800 x 400, keeping the strike on a node at every level
+    let base = SolverConfig { grid: SpaceGrid::Sinh { concentration: 0.1 }, ..SolverConfig::new(100, 50) };
+    match convergence_study(&params, &base, 1.10, 4) {
+        Ok(r) => println!(
+            "Richardson Price at Spot {:.4}: {:.6} +/- {:.1e} (order {:.2}, finest {}x{} error {:.1e})",
+            1.10, r.extrapolated, r.extrapolated_error, r.order, r.levels[3].m, r.levels[3].n, r.error_estimate,
+        ),
+        Err(e) => println!("Convergence study failed: {}", e),
     }
+    // Independent check: inverse stable subordinator Monte Carlo, 200k antithetic paths
+    match price_monte_carlo(&params, &McConfig::new(200_000, 1), 1.10) {
+        Ok(mc) => println!(
+            "Monte Carlo Price at Spot {:.4}: {:.6} +/- {:.6} (95% CI [{:.6}, {:.6}])",
+            1.10, mc.price, mc.std_error, mc.ci_low, mc.ci_high,
+        ),
+        Err(e) => println!("Monte Carlo failed: {}", e),
+    }
+    let gk = garman_kohlhagen(&params, 1.10);
+    println!("Garman-Kohlhagen (alpha = 1) Price at Spot {:.4}: {:.6}", 1.10, gk.price);
+    // Round trip: recover sigma and alpha from the fractional premium, then try an unattainable quote
+    let config = SolverConfig::new(400, 200);
+    match implied_sigma(&params, &config, 1.10, call.price_at(1.10)) {
+        Ok(sigma) => println!("Implied Sigma (alpha = 0.85): {:.6}", sigma),
+        Err(e) => println!("Implied Sigma failed: {}", e),
+    }
+    match implied_alpha(&params, &config, 1.10, call.price_at(1.10)) {
+        Ok(alphas) => println!("Implied Alpha (sigma = 0.15): {:.6?}", alphas),
+        Err(e) => println!("Implied Alpha failed: {}", e),
+    }
+    if let Err(e) = implied_alpha(&params, &config, 1.10, 0.10) {
+        println!("Implied Alpha for premium 0.10: {}", e);
+    }
+    // Same call on USD (domestic) and EUR (foreign) curves bootstrapped from rate_curves.csv, if present
+    match (bootstrap_from_file("rate_curves.csv", "USD"), bootstrap_from_file("rate_curves.csv", "EUR")) {
+        (Ok(usd), Ok(eur)) => {
+            let curves = RateCurves { domestic: Arc::new(usd), foreign: Arc::new(eur) };
+            let curve_params = FxOptionParams { rate_curves: Some(curves), ..params.clone() };
+            let (rd, rf) = curve_params.effective_rates();
+            println!(
+                "Price on Rate Curves (1Y zero rd {:.4}, rf {:.4}) at Spot {:.4}: {:.6} (GK {:.6})",
+                rd, rf, 1.10, solve_fx_tfbs(&curve_params, 400, 200)?.price_at(1.10), garman_kohlhagen(&FxOptionParams { alpha: 1.0, ..curve_params.clone() }, 1.10).price,
+            );
+        }
+        (Err(e), _) | (_, Err(e)) => println!("Rate curves skipped: {}", e),
+    }
+    // Variable-order memory: alpha 0.70 for the first six months, 0.95 afterwards, and a smooth drift
+    let regimes = AlphaSchedule::PiecewiseConstant(vec![(0.0, 0.70), (0.5, 0.95)]);
+    let drifting = AlphaSchedule::from_fn(|t| 0.70 + 0.25 * t);
+    for (name, schedule) in [("piecewise", regimes), ("linear", drifting)] {
+        let schedule_params = FxOptionParams { alpha_schedule: Some(schedule), ..params.clone() };
+        println!("Price with {} alpha(t) at Spot {:.4}: {:.6}", name, 1.10, solve_fx_tfbs(&schedule_params, 400, 200)?.price_at(1.10));
+    }
+    // 25-delta call strike (forward premium-adjusted, the EURUSD convention) under GK and the fractional model
+    let pa = DeltaConvention::ForwardPremiumAdjusted;
+    if let (Ok(k_gk), Ok(k_frac)) = (gk_strike_from_delta(&params, 1.10, 0.25, pa), fractional_strike_from_delta(&params, &config, 1.10, 0.25, pa)) {
+        println!("25D Call Strike (fwd, premium-adjusted): GK {:.4}, fractional {:.4}", k_gk, k_frac);
+    }
+    // Fit (sigma, alpha) to the EURUSD smile in smile_quotes.csv, if present
+    let calibrated = read_quotes("smile_quotes.csv", &params, 1.10)
+        .and_then(|quotes| calibrate_sigma_alpha(&params, &SolverConfig::new(200, 100), 1.10, &quotes));
+    match calibrated {
+        Ok(fit) => println!(
+            "Calibrated sigma {:.4} (+/- {:.4}), alpha {:.4} (+/- {:.4}), RMSE {:.2e} after {} iterations",
+            fit.sigma, fit.sigma_std_err.unwrap_or(f64::NAN), fit.alpha, fit.alpha_std_err.unwrap_or(f64::NAN),
+            fit.rmse, fit.iterations,
+        ),
+        Err(e) => println!("Calibration skipped: {}", e),
+    }
+    if let Some(pos) = call.s_grid.iter().position(|&x| x >= 1.10) {
+        println!(
+            "Greeks at Grid Spot {:.4}: delta {:.4}, gamma {:.4}, theta {:.4}, vega {:.4}, rho_d {:.4}, rho_f {:.4}, dV/dalpha {:.4}",
+            greeks.s_grid[pos], greeks.delta[pos], greeks.gamma[pos], greeks.theta[pos], greeks.vega[pos],
+            greeks.rho_d[pos], greeks.rho_f[pos], greeks.alpha_sens[pos],
+        );
+    }
+    let put = solve_fx_tfbs(&put_params, 400, 200)?;
+    println!("Stable Put Price at Spot {:.4}: {:.6}", 1.10, put.price_at(1.10));
+    // Same put on +/- 5 standard deviations around the forward and strike with zero gamma at both ends
+    let domain_config = SolverConfig {
+        domain: Domain::StdDevs { spot: 1.10, width: 5.0 }, lower_bc: BoundaryCondition::Linearity,
+        upper_bc: BoundaryCondition::Linearity, ..SolverConfig::new(400, 200)
+    };
+    let put_on_domain = solve_fx_tfbs_with(&put_params, &domain_config)?;
+    println!("Put Price (5 sd domain, zero gamma ends) at Spot {:.4}: {:.6}", 1.10, put_on_domain.price_at(1.10));
+    let american_put = solve_fx_tfbs(&american_put_params, 400, 200)?;
+    println!("American Put Price at Spot {:.4}: {:.6}", 1.10, american_put.price_at(1.10));
+    if let Some(Some(s_star)) = american_put.exercise_boundary.last() {
+        println!("American Put Early-Exercise Boundary at Inception: {:.4}", s_star);
+    }
+    // Up-and-out call (barrier 1.30) across memory parameters
+    for alpha in [0.7, 0.85, 0.95] {
+        let uo_call = solve_fx_tfbs(&FxOptionParams { alpha, ..ko_params.clone() }, 400, 200)?;
+        println!("UO Call (B = 1.30, alpha = {:.2}) at Spot {:.4}: {:.6}", alpha, 1.10, uo_call.price_at(1.10));
+    }
+    // A book of 1M EUR calls and puts from 1.00 to 1.20 plus a mistyped trade: one solve per payoff
+    let mut book: Vec<Trade> = (0..=8)
+        .flat_map(|i| [OptionType::Call, OptionType::Put].map(|option_type| Trade {
+            params: FxOptionParams { k: 1.00 + 0.025 * i as f64, option_type, ..params.clone() }, spot: 1.10, notional: 1e6,
+        }))
+        .collect();
+    book.push(Trade { params: FxOptionParams { sigma: -0.15, ..params.clone() }, spot: 1.10, notional: 1e6 });
+    let priced = price_portfolio(&book, &SolverConfig::new(400, 200), 0);
+    println!("Book of {} trades: value {:.2} USD from {} solves", book.len(), priced.value, priced.solves);
+    for (trade, price) in book.iter().zip(&priced.prices) {
+        if let Err(e) = price { println!("Skipped {:?} K = {:.4}: {}", trade.params.option_type, trade.params.k, e); }
+    }
+    Ok(())
 }
-// We obtain an option price of 0.083560 at spot 1.1141  with the following 
+// We obtain an option price of 0.083560 at grid spot 1.1141 (0.075248 interpolated to spot 1.10) with the following 
 // set of params: s_max: 20.0, k: 1.10, t: 1.0, rd: 0.04, rf: 0.02, sigma: 0.15, alpha: 0.85, M = 400, N = 200
 // If the strike price (k) is near the spot, the option is at the money. For an ATM FX option with a 1Y expiration
 // and 10%-15% volatility, a premium of 7-8% of the spot is standard. So the calculation in the eg is:
@@ -0,0 +1,275 @@
+// Monte Carlo cross-check for the fractional PDE. The Caputo problem D^alpha V = L V with the
+// Garman-Kohlhagen operator L is solved by subordination: V(T, S) = E[u(E_T, S)], where u is the
+// classical (alpha = 1) value with E_T years to expiry and E_T is the inverse alpha-stable
+// subordinator at T. So each path draws an operational time E_T = (T / D)^alpha, D one-sided
+// alpha-stable with E[exp(-s D)] = exp(-s^alpha) (Kanter's representation), and runs the usual
+// risk-neutral GBM and domestic discounting over E_T instead of T. At alpha = 1, E_T = T.
+//
+// Barriers are monitored continuously in operational time with the Brownian-bridge crossing
+// probability on `steps` sub-intervals (the time change is continuous, so the spot crosses the
+// barrier exactly when the operational-time path does). The subordination only holds for
+// time-homogeneous coefficients, so rate curves and alpha(t) schedules are rejected, as is
+// American exercise.
+
+use std::f64::consts::PI;
+use std::fmt;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rand_distr::{Exp1, Open01, StandardNormal};
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use crate::fractional_pde::FxOptionParams;
+use crate::payoff::{Barrier, ExerciseStyle};
+
+#[derive(Clone, Debug)]
+pub struct McConfig {
+    pub paths: usize,
+    pub seed: u64,
+    // Pair every normal draw with its negation (same operational time); `paths` counts both members
+    pub antithetic: bool,
+    // Barrier monitoring sub-intervals of the operational time
+    pub steps: usize,
+    // Two-sided confidence level of the reported interval
+    pub confidence: f64,
+}
+
+impl McConfig {
+    pub fn new(paths: usize, seed: u64) -> Self {
+        McConfig { paths, seed, antithetic: true, steps: 100, confidence: 0.95 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct McResult {
+    pub price: f64,
+    pub std_error: f64,
+    pub ci_low: f64,
+    pub ci_high: f64,
+    pub paths: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum McError {
+    // A contract or simulation input outside the range the estimator is defined on
+    InvalidParameter { name: &'static str, value: f64, reason: &'static str },
+    // The representation above does not cover this contract or model feature
+    Unsupported { feature: &'static str },
+    // Too few paths for a variance estimate
+    TooFewPaths { paths: usize },
+}
+
+impl fmt::Display for McError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            McError::InvalidParameter { name, value, reason } => write!(f, "invalid {name} = {value}: {reason}"),
+            McError::Unsupported { feature } => write!(f, "Monte Carlo does not support {feature}"),
+            McError::TooFewPaths { paths } => write!(f, "{paths} paths are too few for a confidence interval"),
+        }
+    }
+}
+
+impl std::error::Error for McError {}
+
+pub fn price_monte_carlo(params: &FxOptionParams, config: &McConfig, spot: f64) -> Result<McResult, McError> {
+    validate(params, config, spot)?;
+    if params.exercise == ExerciseStyle::American { return Err(McError::Unsupported { feature: "American exercise" }); }
+    if params.rate_curves.is_some() { return Err(McError::Unsupported { feature: "rate curves" }); }
+    if params.alpha_schedule.is_some() { return Err(McError::Unsupported { feature: "alpha(t) schedules" }); }
+    if let Some(b) = params.barrier && b.barrier_type.is_knock_in() && b.rebate != 0.0 {
+        return Err(McError::Unsupported { feature: "knock-in rebates" });
+    }
+    let group = if config.antithetic { 2 } else { 1 };
+    let samples = config.paths / group;
+    if samples < 2 { return Err(McError::TooFewPaths { paths: config.paths }); }
+
+    let mut rng = ChaCha20Rng::seed_from_u64(config.seed);
+    let (mut sum, mut sum_sq) = (0.0, 0.0);
+    let mut normals = vec![0.0; config.steps];
+    for _ in 0..samples {
+        let tau = operational_time(params.alpha, params.t, &mut rng);
+        let count = if params.barrier.is_some() { config.steps } else { 1 };
+        for z in normals.iter_mut().take(count) { *z = rng.sample(StandardNormal); }
+        let mut value = path_value(params, spot, tau, &normals[..count], 1.0);
+        if config.antithetic {
+            value = 0.5 * (value + path_value(params, spot, tau, &normals[..count], -1.0));
+        }
+        sum += value;
+        sum_sq += value * value;
+    }
+    let n = samples as f64;
+    let price = sum / n;
+    let std_error = ((sum_sq - n * price * price) / (n - 1.0)).max(0.0).sqrt() / n.sqrt();
+    let z = Normal::new(0.0, 1.0).unwrap().inverse_cdf(0.5 + 0.5 * config.confidence);
+    Ok(McResult { price, std_error, ci_low: price - z * std_error, ci_high: price + z * std_error, paths: samples * group })
+}
+
+// The same contract checks as the PDE solver's, plus the simulation settings
+fn validate(params: &FxOptionParams, config: &McConfig, spot: f64) -> Result<(), McError> {
+    let invalid = |name, value, reason| Err(McError::InvalidParameter { name, value, reason });
+    if !(spot > 0.0 && spot.is_finite()) { return invalid("spot", spot, "spot must be positive"); }
+    if !(params.t > 0.0 && params.t.is_finite()) { return invalid("t", params.t, "expiry must be positive"); }
+    if !(params.k > 0.0 && params.k.is_finite()) { return invalid("k", params.k, "strike must be positive"); }
+    if !(params.sigma > 0.0 && params.sigma.is_finite()) { return invalid("sigma", params.sigma, "volatility must be positive"); }
+    if !params.rd.is_finite() { return invalid("rd", params.rd, "rate must be finite"); }
+    if !params.rf.is_finite() { return invalid("rf", params.rf, "rate must be finite"); }
+    if !(params.alpha > 0.0 && params.alpha <= 1.0) { return invalid("alpha", params.alpha, "fractional order must lie in (0, 1]"); }
+    if let Some(b) = params.barrier && !(b.level > 0.0 && b.level.is_finite()) {
+        return invalid("barrier", b.level, "barrier must be positive");
+    }
+    if let Some(b) = params.barrier && !b.rebate.is_finite() { return invalid("rebate", b.rebate, "rebate must be finite"); }
+    if config.steps == 0 { return invalid("steps", 0.0, "at least one monitoring step is needed"); }
+    if !(config.confidence > 0.0 && config.confidence < 1.0) {
+        return invalid("confidence", config.confidence, "confidence level must lie in (0, 1)");
+    }
+    Ok(())
+}
+
+// E_T = (T / D)^alpha with D drawn by Kanter's formula
+// D = sin(alpha U) / sin(U)^(1/alpha) * (sin((1-alpha) U) / W)^((1-alpha)/alpha), U ~ U(0, pi), W ~ Exp(1)
+fn operational_time(alpha: f64, t: f64, rng: &mut impl Rng) -> f64 {
+    if alpha >= 1.0 { return t; }
+    let u = PI * rng.sample::<f64, _>(Open01);
+    let w: f64 = rng.sample(Exp1);
+    let d = (alpha * u).sin() / u.sin().powf(1.0 / alpha) * ((((1.0 - alpha) * u).sin() / w).powf((1.0 - alpha) / alpha));
+    (t / d).powf(alpha)
+}
+
+// Discounted payoff of one path over operational time tau, with the normals scaled by `sign`
+fn path_value(params: &FxOptionParams, spot: f64, tau: f64, normals: &[f64], sign: f64) -> f64 {
+    let drift = params.rd - params.rf - 0.5 * params.sigma.powi(2);
+    let dt = tau / normals.len() as f64;
+    let vol = params.sigma * dt.sqrt();
+    let mut x = spot.ln();
+    let Some(barrier) = params.barrier else {
+        x += drift * tau + vol * sign * normals[0];
+        return (-params.rd * tau).exp() * params.option_type.payoff(x.exp(), params.k);
+    };
+
+    // Survival probability of the continuously monitored path and the discounted rebate paid at
+    // the first crossing, both accumulated step by step along the bridge
+    let x_b = barrier.level.ln();
+    let up = barrier.barrier_type.is_up();
+    let (mut survival, mut rebate) = (if crossed(up, x, x_b) { 0.0 } else { 1.0 }, 0.0);
+    if survival == 0.0 { rebate = barrier.rebate; }
+    for (j, z) in normals.iter().enumerate() {
+        let x_next = x + drift * dt + vol * sign * z;
+        // Knocked paths still run to expiry for the knock-in payoff
+        if survival > 0.0 {
+            let hit = if crossed(up, x_next, x_b) {
+                1.0
+            } else {
+                (-2.0 * (x_b - x).abs() * (x_b - x_next).abs() / (params.sigma.powi(2) * dt)).exp()
+            };
+            rebate += survival * hit * barrier.rebate * (-params.rd * (j + 1) as f64 * dt).exp();
+            survival *= 1.0 - hit;
+        }
+        x = x_next;
+    }
+    let terminal = (-params.rd * tau).exp() * params.option_type.payoff(x.exp(), params.k);
+    knock_value(barrier, survival, terminal, rebate)
+}
+
+fn crossed(up: bool, x: f64, x_b: f64) -> bool {
+    if up { x >= x_b } else { x <= x_b }
+}
+
+// Knock-outs keep the surviving payoff plus the rebate; knock-ins (no rebate, as in the PDE's
+// in/out parity) pay on the paths that crossed
+fn knock_value(barrier: Barrier, survival: f64, terminal: f64, rebate: f64) -> f64 {
+    if barrier.barrier_type.is_knock_in() { (1.0 - survival) * terminal } else { survival * terminal + rebate }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::caputo::TimeMesh;
+    use crate::fractional_pde::{SolverConfig, solve_fx_tfbs_with};
+    use crate::garman_kohlhagen::garman_kohlhagen;
+    use crate::grid::SpaceGrid;
+    use crate::payoff::{BarrierType, OptionType};
+
+    fn eurusd_call(alpha: f64) -> FxOptionParams {
+        FxOptionParams { alpha, ..FxOptionParams::eurusd_call() }
+    }
+
+    fn assert_within(mc: McResult, reference: f64, what: &str) {
+        let z = (mc.price - reference) / mc.std_error;
+        assert!(z.abs() < 4.0, "{what}: MC {} +/- {} vs {reference} ({z:.1} standard errors)", mc.price, mc.std_error);
+    }
+
+    #[test]
+    fn same_seed_reproduces_the_estimate() {
+        let params = eurusd_call(0.85);
+        let a = price_monte_carlo(&params, &McConfig::new(10_000, 42), 1.10).unwrap();
+        let b = price_monte_carlo(&params, &McConfig::new(10_000, 42), 1.10).unwrap();
+        let c = price_monte_carlo(&params, &McConfig::new(10_000, 43), 1.10).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a.price, c.price);
+        assert!(a.ci_low < a.price && a.price < a.ci_high);
+    }
+
+    #[test]
+    fn monte_carlo_agrees_with_gk_and_the_fractional_pde() {
+        let gk_params = eurusd_call(1.0);
+        let mc = price_monte_carlo(&gk_params, &McConfig::new(100_000, 1), 1.10).unwrap();
+        assert_within(mc, garman_kohlhagen(&gk_params, 1.10).price, "alpha = 1");
+
+        let config = SolverConfig {
+            grid: SpaceGrid::Sinh { concentration: 0.1 }, mesh: TimeMesh::Graded { r: 1.35 }, ..SolverConfig::new(300, 300)
+        };
+        let up_and_out = Barrier { barrier_type: BarrierType::UpAndOut, level: 1.30, rebate: 0.0 };
+        for (params, paths) in [
+            (eurusd_call(0.85), 100_000),
+            (FxOptionParams { option_type: OptionType::Put, ..eurusd_call(0.5) }, 100_000),
+            (FxOptionParams { barrier: Some(up_and_out), ..eurusd_call(0.85) }, 20_000),
+        ] {
+            let mc = price_monte_carlo(&params, &McConfig::new(paths, 2), 1.10).unwrap();
+            let pde = solve_fx_tfbs_with(&params, &config).unwrap().price_at(1.10);
+            assert_within(mc, pde, &format!("{:?} alpha {} barrier {:?}", params.option_type, params.alpha, params.barrier));
+        }
+    }
+
+    #[test]
+    fn bad_inputs_return_errors_instead_of_panicking() {
+        let invalid = |params: FxOptionParams, config: McConfig, spot: f64, name: &str| {
+            match price_monte_carlo(&params, &config, spot) {
+                Err(McError::InvalidParameter { name: got, .. }) => assert_eq!(got, name),
+                other => panic!("expected invalid {name}, got {other:?}"),
+            }
+        };
+        let config = McConfig::new(1_000, 1);
+        invalid(eurusd_call(0.85), config.clone(), 0.0, "spot");
+        invalid(FxOptionParams { sigma: -0.15, ..eurusd_call(0.85) }, config.clone(), 1.10, "sigma");
+        invalid(FxOptionParams { t: 0.0, ..eurusd_call(0.85) }, config.clone(), 1.10, "t");
+        invalid(FxOptionParams { k: f64::NAN, ..eurusd_call(0.85) }, config.clone(), 1.10, "k");
+        invalid(FxOptionParams { rd: f64::INFINITY, ..eurusd_call(0.85) }, config.clone(), 1.10, "rd");
+        invalid(eurusd_call(1.2), config.clone(), 1.10, "alpha");
+        invalid(eurusd_call(0.85), McConfig { confidence: 1.0, ..config.clone() }, 1.10, "confidence");
+        invalid(eurusd_call(0.85), McConfig { confidence: 0.0, ..config.clone() }, 1.10, "confidence");
+        invalid(eurusd_call(0.85), McConfig { steps: 0, ..config.clone() }, 1.10, "steps");
+        let result = price_monte_carlo(&eurusd_call(0.85), &McConfig::new(1, 1), 1.10);
+        assert!(matches!(result, Err(McError::TooFewPaths { paths: 1 })));
+        let rebated_in = Barrier { barrier_type: BarrierType::DownAndIn, level: 0.95, rebate: 0.05 };
+        let result = price_monte_carlo(&FxOptionParams { barrier: Some(rebated_in), ..eurusd_call(0.85) }, &config, 1.10);
+        assert!(matches!(result, Err(McError::Unsupported { .. })));
+    }
+
+    #[test]
+    fn knock_out_rebate_agrees_with_the_pde() {
+        // A rebate paid at the hit adds the same amount to the PDE's barrier node and to every
+        // knocked-out path, so both must move together as it grows
+        let config = SolverConfig {
+            grid: SpaceGrid::Sinh { concentration: 0.1 }, mesh: TimeMesh::Graded { r: 1.35 }, ..SolverConfig::new(300, 300)
+        };
+        for (alpha, rebate) in [(1.0, 0.05), (0.85, 0.05)] {
+            let barrier = Barrier { barrier_type: BarrierType::UpAndOut, level: 1.30, rebate };
+            let params = FxOptionParams { barrier: Some(barrier), ..eurusd_call(alpha) };
+            let mc = price_monte_carlo(&params, &McConfig::new(20_000, 3), 1.10).unwrap();
+            let pde = solve_fx_tfbs_with(&params, &config).unwrap().price_at(1.10);
+            let no_rebate = FxOptionParams { barrier: Some(Barrier { rebate: 0.0, ..barrier }), ..params.clone() };
+            assert!(pde > solve_fx_tfbs_with(&no_rebate, &config).unwrap().price_at(1.10) + 0.01);
+            assert_within(mc, pde, &format!("alpha {alpha} rebate {rebate}"));
+        }
+    }
+}
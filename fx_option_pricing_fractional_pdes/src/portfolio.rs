@@ -0,0 +1,190 @@
+// Portfolio pricing. The fractional GK operator in log-spot has no strike in it, so a vanilla is
+// homogeneous of degree one in (S, K): V(S; K) = K * u(S / K) with u the same contract struck at 1
+// (barrier levels and rebates scaled by 1 / K). Digitals pay a fixed cash amount and are homogeneous
+// of degree zero, V(S; K) = u(S / K). Trades whose unit-strike contracts coincide (same expiry,
+// rates, vol, alpha, payoff type, exercise and scaled barrier) therefore share a single solve, and
+// with it the grid and the factorisation of the implicit step, whatever their strikes and spots.
+//
+// Each group is solved on the union of its trades' domains in moneyness, so every trade keeps at
+// least the room its own solve would have; with one strike per group the grid is the trade's own
+// grid shifted by ln K. Independent groups run on scoped worker threads. Trades failing
+// validation get their own error and the rest of the book is still priced.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::alpha_schedule::AlphaSchedule;
+use crate::fractional_pde::{FxOptionParams, SolverConfig, SolverError, solve_fx_tfbs_with, validate};
+use crate::grid::Domain;
+use crate::payoff::{Barrier, OptionType};
+
+#[derive(Clone, Debug)]
+pub struct Trade {
+    pub params: FxOptionParams,
+    pub spot: f64,
+    // Units of the foreign currency (vanillas) or of the domestic cash amount (digitals)
+    pub notional: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct PortfolioResult {
+    // Unit price of each trade, in input order
+    pub prices: Vec<Result<f64, SolverError>>,
+    // Notional-weighted sum over the trades that priced
+    pub value: f64,
+    // PDE solves performed, one per group
+    pub solves: usize,
+}
+
+// Trades sharing one unit-strike solve, with the moneyness domain that covers all of them
+struct Group {
+    unit: FxOptionParams,
+    trades: Vec<usize>,
+    x_min: f64,
+    x_max: f64,
+}
+
+// Prices every trade with `config`. `threads` = 0 uses all available cores; groups are handed out
+// one at a time, so the result does not depend on the thread count.
+pub fn price_portfolio(trades: &[Trade], config: &SolverConfig, threads: usize) -> PortfolioResult {
+    let mut prices: Vec<Result<f64, SolverError>> = vec![Ok(0.0); trades.len()];
+    let mut groups: Vec<Group> = Vec::new();
+    for (idx, trade) in trades.iter().enumerate() {
+        if !(trade.spot > 0.0 && trade.spot.is_finite()) {
+            prices[idx] = Err(SolverError::InvalidParameter { name: "spot", value: trade.spot, reason: "spot must be positive" });
+            continue;
+        }
+        if let Err(e) = validate(&trade.params, config) {
+            prices[idx] = Err(e);
+            continue;
+        }
+        let unit = unit_strike(&trade.params);
+        let (x_lo, x_hi) = config.domain.bounds(&trade.params);
+        let (x_lo, x_hi) = (x_lo - trade.params.k.ln(), x_hi - trade.params.k.ln());
+        match groups.iter_mut().find(|g| same_contract(&g.unit, &unit)) {
+            Some(g) => {
+                g.trades.push(idx);
+                (g.x_min, g.x_max) = (g.x_min.min(x_lo), g.x_max.max(x_hi));
+            }
+            None => groups.push(Group { unit, trades: vec![idx], x_min: x_lo, x_max: x_hi }),
+        }
+    }
+
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        t => t,
+    }
+    .min(groups.len().max(1));
+    let next = AtomicUsize::new(0);
+    let worker = || {
+        let mut priced = Vec::new();
+        loop {
+            let g = next.fetch_add(1, Ordering::Relaxed);
+            let Some(group) = groups.get(g) else { break };
+            priced.extend(price_group(group, trades, config));
+        }
+        priced
+    };
+    let priced: Vec<(usize, Result<f64, SolverError>)> = if threads == 1 {
+        worker()
+    } else {
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads).map(|_| scope.spawn(worker)).collect();
+            handles.into_iter().flat_map(|h| h.join().expect("portfolio worker panicked")).collect()
+        })
+    };
+    for (idx, price) in priced {
+        prices[idx] = price;
+    }
+
+    let value = prices.iter().zip(trades).filter_map(|(p, t)| p.as_ref().ok().map(|p| t.notional * p)).sum();
+    PortfolioResult { prices, value, solves: groups.len() }
+}
+
+fn price_group(group: &Group, trades: &[Trade], config: &SolverConfig) -> Vec<(usize, Result<f64, SolverError>)> {
+    let domain = Domain::Range { s_min: group.x_min.exp(), s_max: group.x_max.exp() };
+    match solve_fx_tfbs_with(&group.unit, &SolverConfig { domain, ..config.clone() }) {
+        Ok(sol) => group
+            .trades
+            .iter()
+            .map(|&idx| {
+                let p = &trades[idx].params;
+                (idx, Ok(price_scale(p) * sol.price_at(trades[idx].spot / p.k)))
+            })
+            .collect(),
+        Err(e) => group.trades.iter().map(|&idx| (idx, Err(e.clone()))).collect(),
+    }
+}
+
+// K for vanillas, 1 for the cash-settled digitals
+fn price_scale(params: &FxOptionParams) -> f64 {
+    match params.option_type {
+        OptionType::Call | OptionType::Put => params.k,
+        OptionType::DigitalCall | OptionType::DigitalPut => 1.0,
+    }
+}
+
+// The same contract struck at 1, priced in units of price_scale. s_max is left alone; the group's
+// domain replaces it.
+fn unit_strike(params: &FxOptionParams) -> FxOptionParams {
+    let scale = price_scale(params);
+    let barrier = params.barrier.map(|b| Barrier { level: b.level / params.k, rebate: b.rebate / scale, ..b });
+    FxOptionParams { k: 1.0, barrier, ..params.clone() }
+}
+
+fn same_contract(a: &FxOptionParams, b: &FxOptionParams) -> bool {
+    let curves = match (&a.rate_curves, &b.rate_curves) {
+        (None, None) => true,
+        (Some(x), Some(y)) => Arc::ptr_eq(&x.domestic, &y.domestic) && Arc::ptr_eq(&x.foreign, &y.foreign),
+        _ => false,
+    };
+    let schedule = match (&a.alpha_schedule, &b.alpha_schedule) {
+        (None, None) => true,
+        (Some(AlphaSchedule::PiecewiseConstant(x)), Some(AlphaSchedule::PiecewiseConstant(y))) => x == y,
+        (Some(AlphaSchedule::Function(f)), Some(AlphaSchedule::Function(g))) => Arc::ptr_eq(f, g),
+        _ => false,
+    };
+    // Flat rates only matter without curves, and alpha only without a schedule
+    let rates = a.rate_curves.is_some() || (a.rd == b.rd && a.rf == b.rf);
+    let alpha = a.alpha_schedule.is_some() || a.alpha == b.alpha;
+    curves && schedule && rates && alpha && a.t == b.t && a.sigma == b.sigma && a.option_type == b.option_type
+        && a.exercise == b.exercise && a.barrier == b.barrier
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payoff::BarrierType;
+
+    fn eurusd(k: f64, option_type: OptionType) -> FxOptionParams {
+        FxOptionParams { k, option_type, ..FxOptionParams::eurusd_call() }
+    }
+
+    #[test]
+    fn strike_strip_shares_one_solve_per_payoff() {
+        let config = SolverConfig::new(400, 200);
+        let up_and_out = Barrier { barrier_type: BarrierType::UpAndOut, level: 1.30, rebate: 0.0 };
+        let mut params: Vec<FxOptionParams> = Vec::new();
+        for k in [1.00, 1.05, 1.10, 1.15, 1.20] {
+            params.push(eurusd(k, OptionType::Call));
+            params.push(eurusd(k, OptionType::Put));
+        }
+        params.push(FxOptionParams { barrier: Some(up_and_out), ..eurusd(1.10, OptionType::Call) });
+        params.push(FxOptionParams { sigma: -0.15, ..eurusd(1.10, OptionType::Call) });
+        let trades: Vec<Trade> = params.into_iter().map(|params| Trade { params, spot: 1.10, notional: 1e6 }).collect();
+
+        let serial = price_portfolio(&trades, &config, 1);
+        assert_eq!(serial.solves, 3);
+        assert!(matches!(serial.prices[11], Err(SolverError::InvalidParameter { name: "sigma", .. })));
+        for (trade, price) in trades.iter().zip(&serial.prices).take(11) {
+            let direct = solve_fx_tfbs_with(&trade.params, &config).unwrap().price_at(trade.spot);
+            let price = *price.as_ref().unwrap();
+            assert!((price - direct).abs() < 5e-5, "K {} {:?}: {price} vs {direct}", trade.params.k, trade.params.option_type);
+        }
+
+        let threaded = price_portfolio(&trades, &config, 4);
+        assert_eq!(serial.prices, threaded.prices);
+        assert_eq!(serial.value, threaded.value);
+    }
+}
@@ -0,0 +1,137 @@
+use faer::Mat;
+
+// Which linear solver the time stepper uses. The L1 system is tridiagonal, so the banded
+// Thomas factorisation is O(M) in time and memory; the dense LU is kept as a reference path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LinearSolver { Tridiagonal, DenseLu }
+
+// Tridiagonal matrix in band storage: lower[i] = A[i][i-1] (lower[0] unused),
+// diag[i] = A[i][i], upper[i] = A[i][i+1] (upper[size-1] unused)
+#[derive(Clone, Debug)]
+pub struct TridiagonalMatrix {
+    pub lower: Vec<f64>, pub diag: Vec<f64>, pub upper: Vec<f64>,
+}
+
+impl TridiagonalMatrix {
+    pub fn from_constant(size: usize, lower: f64, diag: f64, upper: f64) -> Self {
+        TridiagonalMatrix { lower: vec![lower; size], diag: vec![diag; size], upper: vec![upper; size] }
+    }
+
+    pub fn size(&self) -> usize {
+        self.diag.len()
+    }
+
+    pub fn to_dense(&self) -> Mat<f64> {
+        let size = self.size();
+        let mut a = Mat::<f64>::zeros(size, size);
+        for i in 0..size {
+            a[(i, i)] = self.diag[i];
+            if i > 0 { a[(i, i - 1)] = self.lower[i]; }
+            if i + 1 < size { a[(i, i + 1)] = self.upper[i]; }
+        }
+        a
+    }
+
+    // The same system with the unknowns in reverse order: row i becomes row size - 1 - i
+    pub fn reversed(&self) -> Self {
+        let rev = |v: &[f64]| v.iter().rev().copied().collect();
+        TridiagonalMatrix { lower: rev(&self.upper), diag: rev(&self.diag), upper: rev(&self.lower) }
+    }
+
+    // Thomas algorithm (LU without pivoting), stable for the diagonally dominant L1 system.
+    // Fails with the row of the first zero or non-finite pivot.
+    pub fn factorize(&self) -> Result<TridiagonalLu, usize> {
+        let size = self.size();
+        let mut inv_pivot = vec![0.0; size];
+        let mut upper_mod = vec![0.0; size];
+        for i in 0..size {
+            let pivot = if i == 0 { self.diag[0] } else { self.diag[i] - self.lower[i] * upper_mod[i - 1] };
+            if pivot == 0.0 || !pivot.is_finite() { return Err(i); }
+            inv_pivot[i] = 1.0 / pivot;
+            if i + 1 < size { upper_mod[i] = self.upper[i] * inv_pivot[i]; }
+        }
+        Ok(TridiagonalLu { lower: self.lower.clone(), inv_pivot, upper_mod })
+    }
+}
+
+pub struct TridiagonalLu {
+    lower: Vec<f64>, inv_pivot: Vec<f64>, upper_mod: Vec<f64>,
+}
+
+impl TridiagonalLu {
+    // Solves A x = rhs in place
+    pub fn solve_in_place(&self, rhs: &mut [f64]) {
+        self.forward(rhs);
+        for i in (0..rhs.len() - 1).rev() {
+            rhs[i] -= self.upper_mod[i] * rhs[i + 1];
+        }
+    }
+
+    // Brennan-Schwartz: solves the complementarity problem x >= floor, A x >= rhs, with equality in
+    // one of them on every row, in place. Exact when A is an M-matrix and the rows where x = floor
+    // form one block at the end, because back substitution then meets them first and projects each
+    // onto the floor before any continuation row depends on it.
+    pub fn solve_projected_in_place(&self, rhs: &mut [f64], floor: &[f64]) {
+        self.forward(rhs);
+        let last = rhs.len() - 1;
+        rhs[last] = rhs[last].max(floor[last]);
+        for i in (0..last).rev() {
+            rhs[i] = (rhs[i] - self.upper_mod[i] * rhs[i + 1]).max(floor[i]);
+        }
+    }
+
+    fn forward(&self, rhs: &mut [f64]) {
+        rhs[0] *= self.inv_pivot[0];
+        for i in 1..rhs.len() {
+            rhs[i] = (rhs[i] - self.lower[i] * rhs[i - 1]) * self.inv_pivot[i];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractional_pde::{FxOptionParams, SolverConfig, solve_fx_tfbs_with};
+    use crate::grid::SpaceGrid;
+    use faer::prelude::*;
+
+    #[test]
+    fn thomas_matches_the_dense_lu() {
+        // Diagonally dominant with unequal bands, as on a sinh grid
+        let size = 40;
+        let mut a = TridiagonalMatrix::from_constant(size, 0.0, 0.0, 0.0);
+        for i in 0..size {
+            let x = i as f64 / size as f64;
+            (a.lower[i], a.diag[i], a.upper[i]) = (-0.3 - 0.2 * x, 1.5 + x, -0.4 + 0.1 * x);
+        }
+        let rhs: Vec<f64> = (0..size).map(|i| (i as f64 * 0.7).sin()).collect();
+        let mut x = rhs.clone();
+        a.factorize().unwrap().solve_in_place(&mut x);
+        let dense = a.to_dense().partial_piv_lu().solve(&Mat::<f64>::from_fn(size, 1, |i, _| rhs[i]));
+        for (i, xi) in x.iter().enumerate() {
+            assert!((xi - dense[(i, 0)]).abs() < 1e-14, "row {i}: {xi} vs {}", dense[(i, 0)]);
+        }
+
+        // And through the solver, on both grids
+        for grid in [SpaceGrid::Uniform, SpaceGrid::Sinh { concentration: 0.1 }] {
+            let config = SolverConfig { grid, ..SolverConfig::new(200, 100) };
+            let banded = solve_fx_tfbs_with(&FxOptionParams::eurusd_call(), &config).unwrap();
+            let dense = solve_fx_tfbs_with(&FxOptionParams::eurusd_call(), &SolverConfig { linear_solver: LinearSolver::DenseLu, ..config }).unwrap();
+            let max_diff = banded.prices.iter().zip(&dense.prices).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+            assert!(max_diff < 1e-12, "{grid:?}: max price difference {max_diff}");
+        }
+    }
+
+    #[test]
+    fn zero_or_non_finite_pivot_reports_its_row() {
+        // Row 1's pivot is 1 - 1 * 1 / 1 = 0
+        let mut a = TridiagonalMatrix::from_constant(4, 1.0, 2.0, 1.0);
+        (a.diag[0], a.diag[1]) = (1.0, 1.0);
+        assert!(matches!(a.factorize(), Err(1)));
+        let mut a = TridiagonalMatrix::from_constant(4, -1.0, 3.0, -1.0);
+        a.diag[3] = f64::NAN;
+        assert!(matches!(a.factorize(), Err(3)));
+        // Reversal turns the last row into the first
+        assert!(matches!(a.reversed().factorize(), Err(0)));
+    }
+}